@@ -13,8 +13,6 @@ pub struct Assets {
     pub ground: Texture2D,
 
     pub meter_full: Texture2D,
-    pub meter_2: Texture2D,
-    pub meter_1: Texture2D,
     pub meter_empty: Texture2D,
 
     pub cloud: Texture2D,
@@ -33,6 +31,14 @@ pub struct Assets {
     pub menu_button_h: Texture2D,
     pub menu_bg: Texture2D,
 
+    pub landing_crater: Texture2D,
+    pub landing_puddle: Texture2D,
+    pub landing_haystack: Texture2D,
+    pub landing_flowers: Texture2D,
+    pub landing_trampoline: Texture2D,
+    pub landing_crowd: Texture2D,
+    pub landing_parade: Texture2D,
+
     pub font: Font,
 
     pub sfx_hit: Sound,
@@ -40,6 +46,13 @@ pub struct Assets {
     pub sfx_fling: Sound,
     pub sfx_boost: Sound,
     pub sfx_end: Sound,
+    pub sfx_crash: Sound,
+    pub sfx_splash: Sound,
+    pub sfx_rustle: Sound,
+    pub sfx_chime: Sound,
+    pub sfx_boing: Sound,
+    pub sfx_cheer: Sound,
+    pub sfx_fanfare: Sound,
 }
 
 impl Assets {
@@ -54,8 +67,6 @@ impl Assets {
             ground: Texture2D::from_file_with_format(include_bytes!("../assets/ground.png"), None),
 
             meter_full: Texture2D::from_file_with_format(include_bytes!("../assets/meter_full.png"), None),
-            meter_2: Texture2D::from_file_with_format(include_bytes!("../assets/meter_2.png"), None),
-            meter_1: Texture2D::from_file_with_format(include_bytes!("../assets/meter_1.png"), None),
             meter_empty: Texture2D::from_file_with_format(include_bytes!("../assets/meter_empty.png"), None),
 
             cloud: Texture2D::from_file_with_format(include_bytes!("../assets/cloud.png"), None),
@@ -74,6 +85,17 @@ impl Assets {
             menu_button_h: Texture2D::from_file_with_format(include_bytes!("../assets/menu_button_h.png"), None),
             menu_bg: Texture2D::from_file_with_format(include_bytes!("../assets/menu_bg.png"), None),
 
+            landing_crater: Texture2D::from_file_with_format(include_bytes!("../assets/landing_crater.png"), None),
+            landing_puddle: Texture2D::from_file_with_format(include_bytes!("../assets/landing_puddle.png"), None),
+            landing_haystack: Texture2D::from_file_with_format(include_bytes!("../assets/landing_haystack.png"), None),
+            landing_flowers: Texture2D::from_file_with_format(include_bytes!("../assets/landing_flowers.png"), None),
+            landing_trampoline: Texture2D::from_file_with_format(
+                include_bytes!("../assets/landing_trampoline.png"),
+                None,
+            ),
+            landing_crowd: Texture2D::from_file_with_format(include_bytes!("../assets/landing_crowd.png"), None),
+            landing_parade: Texture2D::from_file_with_format(include_bytes!("../assets/landing_parade.png"), None),
+
             font: load_ttf_font_from_bytes(include_bytes!("../assets/UbuntuMono-B.ttf")).unwrap(),
 
             sfx_hit: load_sound_from_bytes(include_bytes!("../assets/hit.wav"))
@@ -91,6 +113,27 @@ impl Assets {
             sfx_end: load_sound_from_bytes(include_bytes!("../assets/end.wav"))
                 .await
                 .unwrap(),
+            sfx_crash: load_sound_from_bytes(include_bytes!("../assets/landing_crash.wav"))
+                .await
+                .unwrap(),
+            sfx_splash: load_sound_from_bytes(include_bytes!("../assets/landing_splash.wav"))
+                .await
+                .unwrap(),
+            sfx_rustle: load_sound_from_bytes(include_bytes!("../assets/landing_rustle.wav"))
+                .await
+                .unwrap(),
+            sfx_chime: load_sound_from_bytes(include_bytes!("../assets/landing_chime.wav"))
+                .await
+                .unwrap(),
+            sfx_boing: load_sound_from_bytes(include_bytes!("../assets/landing_boing.wav"))
+                .await
+                .unwrap(),
+            sfx_cheer: load_sound_from_bytes(include_bytes!("../assets/landing_cheer.wav"))
+                .await
+                .unwrap(),
+            sfx_fanfare: load_sound_from_bytes(include_bytes!("../assets/landing_fanfare.wav"))
+                .await
+                .unwrap(),
         }
     }
 }
@@ -1,60 +1,150 @@
-use macroquad::{prelude::*, rand::gen_range};
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::{assets::Assets, player::Player, HDirection};
+use crate::{
+    assets::Assets,
+    draw_hitbox,
+    player::{Player, PLAYER_DIM},
+    rng::Rng,
+    world::Viewport,
+    HDirection,
+};
 const BIRD_SPEED: f32 = 120.0;
 const BIRD_PREDICTION_TIME: f32 = 5.0;
-const BIRD_SIZE: f32 = 30.0;
+pub const BIRD_SIZE: f32 = 30.0;
+const DIVE_SPEED: f32 = 220.0;
+const DIVE_WINDUP: f32 = 0.75;
+const FLOCK_SPEED: f32 = 90.0;
+const FLOCK_SIZE: usize = 5;
+const FLOCK_SPACING: (f32, f32) = (34.0, 26.0);
+const ORBIT_RADIUS: f32 = 90.0;
+const ORBIT_SPEED: f32 = 2.5;
+const ORBIT_TIME: f32 = 3.0;
+const STRIKE_SPEED: f32 = 200.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BirdKind {
+    Basic,
+    Diver,
+    Flock,
+    Circler,
+}
 
 #[derive(Debug)]
 enum State {
     Arrival,
     Target,
+    Circling { angle: f32, radius: f32, timer: f32 },
     Flight,
 }
 
 pub struct Bird {
+    kind: BirdKind,
     state: State,
     move_dir: HDirection,
     position: Vec2,
+    last_position: Vec2,
     velocity: Vec2,
     target_pos: Vec2,
     pause_timer: f32,
     pub hitbox: Rect,
     animation_timer: f32,
+    wrapped: bool,
+    /// Whether the bird has been all the way inside the play area. Birds start off to the side, and shouldn't
+    /// wrap around before they've come in.
+    entered: bool,
 }
 
 impl Bird {
-    pub fn spawn(y_pos: f32, move_dir: HDirection) -> Self {
+    pub fn spawn(kind: BirdKind, y_pos: f32, move_dir: HDirection, viewport: Viewport) -> Self {
         let (x_pos, velocity) = match move_dir {
-            HDirection::Left => (screen_width() + 25.0, vec2(-BIRD_SPEED, 0.0)),
+            HDirection::Left => (viewport.width + 25.0, vec2(-BIRD_SPEED, 0.0)),
             HDirection::Right => (-25.0, vec2(BIRD_SPEED, 0.0)),
         };
 
         Self {
+            kind,
             state: State::Arrival,
             move_dir,
             position: vec2(x_pos, y_pos),
+            last_position: vec2(x_pos, y_pos),
             velocity,
             target_pos: Vec2::default(),
             pause_timer: 0.0,
             hitbox: Rect::new(x_pos, y_pos, BIRD_SIZE, BIRD_SIZE),
             animation_timer: 0.0,
+            wrapped: false,
+            entered: false,
         }
     }
 
-    pub fn update(&mut self, player: &Player, elapsed: f32) {
+    pub fn preview(kind: BirdKind, position: Vec2, move_dir: HDirection, viewport: Viewport) -> Self {
+        let mut bird = Self::spawn(kind, position.y, move_dir, viewport);
+        bird.position = position;
+        bird.last_position = position;
+        bird.update_hitbox();
+        bird
+    }
+
+    pub fn flock(y_pos: f32, move_dir: HDirection, viewport: Viewport) -> Vec<Self> {
+        let (back, velocity) = match move_dir {
+            HDirection::Left => (1.0, vec2(-FLOCK_SPEED, 0.0)),
+            HDirection::Right => (-1.0, vec2(FLOCK_SPEED, 0.0)),
+        };
+        (0..FLOCK_SIZE)
+            .map(|i| {
+                // Alternate between the two arms of the V, each bird further back than the last pair
+                let rank = i.div_ceil(2) as f32;
+                let arm = if i % 2 == 0 { 1.0 } else { -1.0 };
+                let mut bird = Self::spawn(BirdKind::Flock, y_pos, move_dir, viewport);
+                bird.position += vec2(back * rank * FLOCK_SPACING.0, arm * rank * FLOCK_SPACING.1);
+                bird.last_position = bird.position;
+                bird.velocity = velocity;
+                bird.state = State::Flight;
+                bird.update_hitbox();
+                bird
+            })
+            .collect()
+    }
+
+    pub fn update(&mut self, player: &Player, viewport: Viewport, rng: &mut Rng, elapsed: f32) {
+        self.last_position = self.position;
         match self.state {
             State::Arrival => {
                 let in_h_pos = match self.move_dir {
-                    HDirection::Left => self.position.x < screen_width() - BIRD_SIZE,
+                    HDirection::Left => self.position.x < viewport.width - BIRD_SIZE,
                     HDirection::Right => self.position.x > BIRD_SIZE,
                 };
                 let in_v_pos = player.position.y > self.position.y;
-                match (in_h_pos, in_v_pos) {
-                    (true, true) => self.enter_state(player, State::Target),
-                    (true, false) => self.velocity = vec2(0.0, -BIRD_SPEED),
-                    (false, true) => self.velocity.y = 0.0,
-                    (false, false) => {}
+                match (self.kind, in_h_pos, in_v_pos) {
+                    (BirdKind::Diver, true, _) => self.enter_state(player, State::Target, rng),
+                    (BirdKind::Diver, false, _) => {}
+                    (BirdKind::Circler, true, true) => {
+                        let offset = self.position - player_center(player);
+                        let state = State::Circling {
+                            angle: offset.y.atan2(offset.x),
+                            radius: offset.length(),
+                            timer: ORBIT_TIME,
+                        };
+                        self.enter_state(player, state, rng);
+                    }
+                    (_, true, true) => self.enter_state(player, State::Target, rng),
+                    (_, true, false) => self.velocity = vec2(0.0, -BIRD_SPEED),
+                    (_, false, true) => self.velocity.y = 0.0,
+                    (_, false, false) => {}
+                }
+            }
+            State::Circling {
+                ref mut angle,
+                ref mut radius,
+                ref mut timer,
+            } => {
+                *angle += ORBIT_SPEED * elapsed;
+                *radius += (ORBIT_RADIUS - *radius) * f32::min(2.0 * elapsed, 1.0);
+                *timer -= elapsed;
+                self.position = player_center(player) + vec2(angle.cos(), angle.sin()) * *radius;
+                if *timer < 0.0 {
+                    self.enter_state(player, State::Flight, rng);
                 }
             }
             State::Target => {
@@ -63,18 +153,48 @@ impl Bird {
                 }
                 self.pause_timer -= elapsed;
                 if self.pause_timer < 0.0 {
-                    self.enter_state(player, State::Flight);
+                    self.enter_state(player, State::Flight, rng);
                 }
             }
             State::Flight => {}
         }
         self.animation_timer += elapsed;
         self.position += self.velocity * elapsed;
+        if self.position.x >= 0.0 && self.position.x + 45.0 <= viewport.width {
+            self.entered = true;
+        }
+        if let (State::Flight, true, false) = (&self.state, self.entered, self.wrapped) {
+            let shift = viewport.wrap_shift(self.position.x, 45.0);
+            if shift != 0.0 {
+                self.position.x += shift;
+                self.last_position.x += shift;
+                self.wrapped = true;
+            }
+        }
         self.update_hitbox();
     }
 
-    pub fn draw(&self, assets: &mut Assets) {
-        let texture = match (self.animation_timer * 10.0) as usize % 2 {
+    pub fn push(&mut self, wind: Vec2, elapsed: f32) {
+        if let State::Flight = self.state {
+            self.velocity += wind * elapsed;
+        }
+    }
+
+    pub fn draw(&self, assets: &mut Assets, alpha: f32) {
+        let position = self.last_position.lerp(self.position, alpha);
+        // Flap faster when about to attack
+        let flap_rate = match (self.kind, &self.state) {
+            (BirdKind::Diver, State::Target | State::Flight) => 20.0,
+            (BirdKind::Circler, State::Circling { .. }) => 16.0,
+            _ => 10.0,
+        };
+        let tint = match self.kind {
+            BirdKind::Basic => WHITE,
+            BirdKind::Diver => Color::from_rgba(255, 160, 150, 255),
+            BirdKind::Flock => Color::from_rgba(170, 205, 255, 255),
+            BirdKind::Circler => Color::from_rgba(205, 165, 255, 255),
+        };
+        let texture = match (self.animation_timer * flap_rate) as usize % 2 {
             0 => assets.bird_1,
             _ => assets.bird_2,
         };
@@ -84,21 +204,19 @@ impl Bird {
         };
         draw_texture_ex(
             texture,
-            self.position.x,
-            self.position.y,
-            WHITE,
+            position.x,
+            position.y,
+            tint,
             DrawTextureParams {
                 flip_x: flip,
                 ..Default::default()
             },
         );
-        // draw_rectangle(
-        //     self.hitbox.x,
-        //     self.hitbox.y,
-        //     self.hitbox.w,
-        //     self.hitbox.h,
-        //     Color::from_rgba(255, 0, 0, 128),
-        // );
+        draw_hitbox(&self.hitbox);
+    }
+
+    pub fn in_flight(&self) -> bool {
+        matches!(self.state, State::Flight)
     }
 
     fn update_hitbox(&mut self) {
@@ -106,16 +224,24 @@ impl Bird {
         self.hitbox.y = self.position.y + (45.0 - self.hitbox.h) / 2.0;
     }
 
-    fn enter_state(&mut self, player: &Player, state: State) {
-        match state {
-            State::Arrival => {}
-            State::Target => {
+    fn enter_state(&mut self, player: &Player, state: State, rng: &mut Rng) {
+        match (self.kind, &state) {
+            (_, State::Arrival) => {}
+            (BirdKind::Diver, State::Target) => {
+                self.target_pos = player_center(player) + player.velocity * DIVE_WINDUP;
+                self.velocity = Vec2::default();
+                self.pause_timer = DIVE_WINDUP + rng.gen_range(-0.1, 0.1);
+            }
+            (_, State::Target) => {
                 self.target_player(player);
                 self.velocity = vec2(0.0, BIRD_SPEED);
                 self.pause_timer = BIRD_PREDICTION_TIME - (self.target_pos.x - self.position.x).abs() / BIRD_SPEED;
-                self.pause_timer += gen_range(-0.25, 0.25);
+                self.pause_timer += rng.gen_range(-0.25, 0.25);
             }
-            State::Flight => match self.move_dir {
+            (_, State::Circling { .. }) => self.velocity = Vec2::default(),
+            (BirdKind::Diver, State::Flight) => self.fly_towards(self.target_pos, DIVE_SPEED),
+            (BirdKind::Circler, State::Flight) => self.fly_towards(player_center(player), STRIKE_SPEED),
+            (_, State::Flight) => match self.move_dir {
                 HDirection::Left => self.velocity = vec2(-BIRD_SPEED, 0.0),
                 HDirection::Right => self.velocity = vec2(BIRD_SPEED, 0.0),
             },
@@ -123,6 +249,16 @@ impl Bird {
         self.state = state;
     }
 
+    fn fly_towards(&mut self, target: Vec2, speed: f32) {
+        let center = self.position + vec2(BIRD_SIZE, BIRD_SIZE) / 2.0;
+        self.velocity = (target - center).normalize_or_zero() * speed;
+        self.move_dir = if self.velocity.x < 0.0 {
+            HDirection::Left
+        } else {
+            HDirection::Right
+        };
+    }
+
     fn target_player(&mut self, player: &Player) {
         // player's position in 2 sec
         self.target_pos = player.position + player.velocity * BIRD_PREDICTION_TIME;
@@ -142,3 +278,36 @@ impl Bird {
         self.state = State::Flight;
     }
 }
+
+fn player_center(player: &Player) -> Vec2 {
+    player.position + vec2(PLAYER_DIM.0, PLAYER_DIM.1) / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flock_flies_in_before_wrapping() {
+        let viewport = Viewport {
+            width: 400.0,
+            height: 640.0,
+            wrap: true,
+        };
+        let player = Player::new(vec2(200.0, 600.0), 3, 3, 1.0, 1.0);
+        let mut rng = Rng::new(0);
+        let mut flock = Bird::flock(100.0, HDirection::Right, viewport);
+        for _ in 0..60 {
+            for bird in flock.iter_mut() {
+                let before = bird.position.x;
+                bird.update(&player, viewport, &mut rng, 1.0 / 120.0);
+                assert!(
+                    bird.position.x > before,
+                    "bird jumped from {} to {}",
+                    before,
+                    bird.position.x
+                );
+            }
+        }
+    }
+}
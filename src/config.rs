@@ -0,0 +1,292 @@
+use std::{collections::HashMap, fs, io::ErrorKind, sync::OnceLock};
+
+use enum_map::{enum_map, EnumMap};
+use macroquad::prelude::*;
+use serde::{
+    de::{DeserializeOwned, IgnoredAny},
+    Deserialize,
+};
+
+use crate::{
+    keys::{key_name, parse_key},
+    world::{Input, Sound},
+};
+
+pub const CONFIG_PATH: &str = "config.ron";
+/// Bindings saved from the controls screen, which take precedence over the ones in the config.
+pub const BINDINGS_PATH: &str = "bindings.ron";
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+pub struct Config {
+    pub window_width: i32,
+    pub window_height: i32,
+    pub bindings: EnumMap<Input, Vec<KeyCode>>,
+    pub master_volume: f32,
+    pub sound_volumes: EnumMap<Sound, f32>,
+    pub difficulty: String,
+    pub show_ghost: bool,
+    pub debug: DebugConfig,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DebugConfig {
+    pub hitboxes: bool,
+    pub fps: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            window_width: 400,
+            window_height: 640,
+            bindings: enum_map! {
+                Input::Up => vec![KeyCode::W, KeyCode::Up],
+                Input::Down => vec![KeyCode::S, KeyCode::Down],
+                Input::Left => vec![KeyCode::A, KeyCode::Left],
+                Input::Right => vec![KeyCode::D, KeyCode::Right],
+                Input::BoostLeft => vec![KeyCode::Q, KeyCode::RightControl],
+                Input::BoostRight => vec![KeyCode::E, KeyCode::Kp0],
+            },
+            master_volume: 1.0,
+            sound_volumes: enum_map! { _ => 1.0 },
+            difficulty: "Normal".to_string(),
+            show_ghost: true,
+            debug: DebugConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: &str) -> (Self, Vec<String>) {
+        match fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text),
+            Err(e) if e.kind() == ErrorKind::NotFound => (Self::default(), Vec::new()),
+            Err(e) => (Self::default(), vec![e.to_string()]),
+        }
+    }
+
+    fn parse(text: &str) -> (Self, Vec<String>) {
+        let mut config = Self::default();
+        let fields = match split_fields(text) {
+            Ok(fields) => fields,
+            Err(e) => return (config, vec![e]),
+        };
+
+        let mut errors = Vec::new();
+        for (name, value) in fields {
+            let result = match name {
+                "window_width" => read(value, &mut config.window_width),
+                "window_height" => read(value, &mut config.window_height),
+                "bindings" => read_bindings(value, &mut config.bindings),
+                "master_volume" => read(value, &mut config.master_volume),
+                "sound_volumes" => read_map(value, &mut config.sound_volumes),
+                "difficulty" => read(value, &mut config.difficulty),
+                "show_ghost" => read(value, &mut config.show_ghost),
+                "debug" => read(value, &mut config.debug),
+                _ => Err("unknown field".to_string()),
+            };
+            if let Err(e) = result {
+                errors.push(format!("{}: {}", name, e));
+            }
+        }
+        (config, errors)
+    }
+}
+
+pub fn config() -> &'static Config {
+    CONFIG.get_or_init(|| {
+        let (config, errors) = Config::load(CONFIG_PATH);
+        if !errors.is_empty() {
+            error!(
+                "Ignoring invalid settings in {}:\n  {}",
+                CONFIG_PATH,
+                errors.join("\n  ")
+            );
+        }
+        config
+    })
+}
+
+pub fn load_bindings() -> EnumMap<Input, Vec<KeyCode>> {
+    let mut bindings = config().bindings.clone();
+    match fs::read_to_string(BINDINGS_PATH) {
+        Ok(text) => {
+            if let Err(e) = read_bindings(&text, &mut bindings) {
+                error!("Ignoring invalid bindings in {}: {}", BINDINGS_PATH, e);
+            }
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => error!("Could not read {}: {}", BINDINGS_PATH, e),
+    }
+    bindings
+}
+
+pub fn save_bindings(bindings: &EnumMap<Input, Vec<KeyCode>>) -> Result<(), String> {
+    let names: EnumMap<Input, Vec<String>> = enum_map! {
+        input => bindings[input].iter().map(|key| key_name(*key)).collect(),
+    };
+    let text = ron::ser::to_string_pretty(&names, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+    fs::write(BINDINGS_PATH, text).map_err(|e| format!("could not write {}: {}", BINDINGS_PATH, e))
+}
+
+fn read<T: DeserializeOwned>(value: &str, field: &mut T) -> Result<(), String> {
+    *field = ron::from_str(value).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn read_map<K, V>(value: &str, field: &mut EnumMap<K, V>) -> Result<(), String>
+where
+    K: enum_map::EnumArray<V> + DeserializeOwned + std::hash::Hash + Eq,
+    V: DeserializeOwned,
+{
+    let values: HashMap<K, V> = ron::from_str(value).map_err(|e| e.to_string())?;
+    for (key, value) in values {
+        field[key] = value;
+    }
+    Ok(())
+}
+
+fn read_bindings(value: &str, bindings: &mut EnumMap<Input, Vec<KeyCode>>) -> Result<(), String> {
+    let names = ron::from_str(value).map_err(|e| e.to_string())?;
+    apply_bindings(names, bindings)
+}
+
+fn apply_bindings(
+    names: HashMap<Input, Vec<String>>,
+    bindings: &mut EnumMap<Input, Vec<KeyCode>>,
+) -> Result<(), String> {
+    let mut unknown = Vec::new();
+    for (input, names) in names {
+        let keys: Vec<KeyCode> = names
+            .iter()
+            .filter_map(|name| {
+                let key = parse_key(name);
+                if key.is_none() {
+                    unknown.push(name.clone());
+                }
+                key
+            })
+            .collect();
+        if !keys.is_empty() {
+            bindings[input] = keys;
+        }
+    }
+    if unknown.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("unknown keys {}", unknown.join(", ")))
+    }
+}
+
+/// Splits a RON struct into the source text of each of its fields, so that one bad field doesn't hide the others.
+/// ron itself steps over each value, so only the struct's own brackets, names and commas are handled here.
+fn split_fields(text: &str) -> Result<Vec<(&str, &str)>, String> {
+    let mut rest = skip_whitespace(text)?
+        .strip_prefix('(')
+        .ok_or("expected a struct of settings, like `(master_volume: 0.5)`")?;
+    let mut fields = Vec::new();
+    loop {
+        rest = skip_whitespace(rest)?;
+        if let Some(after) = rest.strip_prefix(')') {
+            if !skip_whitespace(after)?.is_empty() {
+                return Err("unexpected text after the settings".to_string());
+            }
+            return Ok(fields);
+        }
+        let (name, after) = rest
+            .split_once(':')
+            .ok_or_else(|| format!("expected `name: value`, found `{}`", rest.trim()))?;
+        let name = name.trim();
+        let mut deserializer = ron::Deserializer::from_str(after).map_err(|e| format!("{}: {}", name, e))?;
+        IgnoredAny::deserialize(&mut deserializer).map_err(|e| format!("{}: {}", name, e))?;
+        let (value, after) = after.split_at(after.len() - deserializer.remainder().len());
+        fields.push((name, value.trim()));
+
+        rest = skip_whitespace(after)?;
+        match rest.strip_prefix(',') {
+            Some(after) => rest = after,
+            None if rest.starts_with(')') => {}
+            None => return Err(format!("expected `,` after {}", name)),
+        }
+    }
+}
+
+fn skip_whitespace(text: &str) -> Result<&str, String> {
+    let deserializer = ron::Deserializer::from_str(text).map_err(|e| e.to_string())?;
+    Ok(&text[text.len() - deserializer.remainder().len()..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Config {
+        let (config, errors) = Config::parse(text);
+        assert!(errors.is_empty(), "unexpected errors {:?}", errors);
+        config
+    }
+
+    #[test]
+    fn missing_fields_keep_their_defaults() {
+        let config = parse("(show_ghost: false, window_width: 480, debug: (fps: true))");
+        assert!(!config.show_ghost);
+        assert_eq!((config.window_width, config.window_height), (480, 640));
+        assert!(config.debug.fps && !config.debug.hitboxes);
+        assert_eq!(config.difficulty, "Normal");
+        assert_eq!(config.bindings[Input::Up], vec![KeyCode::W, KeyCode::Up]);
+    }
+
+    #[test]
+    fn partial_maps_only_change_what_they_list() {
+        let text = r#"
+            // Comments are allowed, even with (brackets, and: colons)
+            (
+                bindings: { Up: ["I"], Left: ["J", "Left"] },
+                /* here too */ sound_volumes: { Hit: 0.5 },
+                difficulty: "Hard, really (no)",
+            )
+        "#;
+        let config = parse(text);
+        assert_eq!(config.bindings[Input::Up], vec![KeyCode::I]);
+        assert_eq!(config.bindings[Input::Left], vec![KeyCode::J, KeyCode::Left]);
+        assert_eq!(config.bindings[Input::Down], vec![KeyCode::S, KeyCode::Down]);
+        assert_eq!(config.sound_volumes[Sound::Hit], 0.5);
+        assert_eq!(config.sound_volumes[Sound::Fling], 1.0);
+        assert_eq!(config.difficulty, "Hard, really (no)");
+    }
+
+    #[test]
+    fn bad_fields_are_all_reported_and_the_rest_kept() {
+        let text = r#"(
+            master_volume: "loud",
+            show_ghost: false,
+            bindings: { Up: ["NotAKey"] },
+            window_height: 720,
+            colour: 3,
+        )"#;
+        let (config, errors) = Config::parse(text);
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors[0].starts_with("master_volume:"));
+        assert!(errors[1].starts_with("bindings:") && errors[1].contains("NotAKey"));
+        assert!(errors[2].starts_with("colour:"));
+        assert_eq!(config.master_volume, 1.0);
+        assert!(!config.show_ghost);
+        assert_eq!(config.window_height, 720);
+    }
+
+    #[test]
+    fn broken_files_fall_back_to_defaults() {
+        for text in [
+            "(show_ghost: true",
+            "show_ghost: false",
+            "(show_ghost false)",
+            "(show_ghost: [)",
+        ] {
+            let (config, errors) = Config::parse(text);
+            assert_eq!(errors.len(), 1, "{} gave {:?}", text, errors);
+            assert!(config.show_ghost);
+        }
+    }
+}
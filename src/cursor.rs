@@ -1,40 +1,63 @@
 use enum_map::EnumMap;
 use macroquad::prelude::*;
 
-use crate::{fling::FlingThing, scenes::game_scene::Sound};
+use crate::{
+    fling::{launch_velocity, FlingThing, Physics, THING_DIM},
+    wind::WindZone,
+    world::{FrameInput, Sound, Viewport},
+};
 
 pub struct Cursor {
     position: Vec2,
     click_position: Option<Vec2>,
     selected_index: Option<usize>,
+    trajectory: Vec<Vec2>,
 }
 
 impl Cursor {
     pub fn new() -> Self {
         let mouse_position = mouse_position();
+        Self::at(Vec2::new(mouse_position.0, mouse_position.1))
+    }
+
+    pub fn at(position: Vec2) -> Self {
         Self {
-            position: Vec2::new(mouse_position.0, mouse_position.1),
+            position,
             click_position: None,
             selected_index: None,
+            trajectory: Vec::new(),
         }
     }
 
-    pub fn update(&mut self, camera: &Camera2D, fling_things: &mut Vec<FlingThing>, sounds: &mut EnumMap<Sound, bool>) {
-        (self.position.x, self.position.y) = mouse_position();
-        self.position = camera.screen_to_world(self.position);
+    pub fn update(
+        &mut self,
+        input: &FrameInput,
+        fling_things: &mut [FlingThing],
+        physics: &Physics,
+        wind_zones: &[WindZone],
+        viewport: Viewport,
+        sounds: &mut EnumMap<Sound, bool>,
+    ) {
+        self.position = input.cursor;
+        self.trajectory.clear();
 
-        if is_mouse_button_pressed(MouseButton::Left) {
+        if input.mouse_pressed {
             self.selected_index = fling_things
                 .iter()
                 .position(|thing| thing.hitbox.contains(self.position));
             self.click_position = Some(self.position);
-        } else if !is_mouse_button_down(MouseButton::Left) {
+        } else if !input.mouse_down {
             if let (Some(index), Some(point)) = (self.selected_index, self.click_position) {
                 fling_things[index].fling(point - self.position);
                 sounds[Sound::Fling] = true;
             }
             self.click_position = None;
             self.selected_index = None;
+        } else if let (Some(index), Some(point)) = (self.selected_index, self.click_position) {
+            let thing = &fling_things[index];
+            if let (false, Some(velocity)) = (thing.flung(), launch_velocity(point - self.position)) {
+                self.trajectory = physics.trajectory(thing.position, velocity, wind_zones, viewport);
+            }
         }
     }
 
@@ -57,12 +80,8 @@ impl Cursor {
                 3.0,
                 MAROON,
             );
-            let velocity = click_position - self.position;
-            if velocity.x.abs() > 50.0 || velocity.y.abs() > 50.0 {
-                let trajectory = calc_trajectory(click_position, velocity);
-                for point in trajectory {
-                    draw_circle(point.x, point.y, 3.0, MAROON);
-                }
+            for point in self.trajectory.iter() {
+                draw_circle(point.x + THING_DIM.0 / 2.0, point.y + THING_DIM.1 / 2.0, 3.0, MAROON);
             }
         }
     }
@@ -71,13 +90,3 @@ impl Cursor {
         self.selected_index.is_some()
     }
 }
-
-fn calc_trajectory(start: Vec2, velocity: Vec2) -> Vec<Vec2> {
-    let mut points = Vec::new();
-    let mut t = 0.0;
-    while t < 5.0 {
-        t += 0.25;
-        points.push(start + velocity * t);
-    }
-    points
-}
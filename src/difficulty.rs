@@ -0,0 +1,272 @@
+use std::{fs, path::Path};
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{bird::BirdKind, lightning::LightningPattern, wave::WaveScript};
+
+pub const DIFFICULTY_DIR: &str = "difficulties";
+
+const BUILT_IN: [&str; 6] = [
+    include_str!("../assets/difficulties/normal.ron"),
+    include_str!("../assets/difficulties/hard.ron"),
+    include_str!("../assets/difficulties/wrap.ron"),
+    include_str!("../assets/difficulties/gauntlet.ron"),
+    include_str!("../assets/difficulties/storm.ron"),
+    include_str!("../assets/difficulties/tempest.ron"),
+];
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Difficulty {
+    pub name: String,
+    /// Seconds between birds, randomly offset by up to `bird_jitter` either way.
+    pub bird_interval: f32,
+    pub bird_jitter: f32,
+    /// Kinds of bird to pick from at random. Listing a kind more than once makes it more likely.
+    #[serde(default = "default_bird_kinds")]
+    pub bird_kinds: Vec<BirdKind>,
+    pub lightning_interval: f32,
+    pub lightning_jitter: f32,
+    #[serde(default = "default_max_lightning")]
+    pub max_lightning: usize,
+    #[serde(default = "default_lightning_patterns")]
+    pub lightning_patterns: Vec<LightningPattern>,
+    pub level_depth: f32,
+    pub cloud_gap: (f32, f32),
+    /// Share of its speed a flung cloud loses per second.
+    #[serde(default)]
+    pub cloud_drag: f32,
+    #[serde(default)]
+    pub cloud_gravity: f32,
+    pub gold_cloud_ratio: f32,
+    #[serde(default)]
+    pub rainbow_ratio: f32,
+    pub balloons: usize,
+    /// Most balloons the player can have from balloon pickups. Left out or 0 means the same as `balloons`.
+    #[serde(default)]
+    pub max_balloons: usize,
+    #[serde(default)]
+    pub balloon_ratio: f32,
+    /// Booster fuel at the start, where one unit is the shortest boost.
+    pub fuel: f32,
+    #[serde(default)]
+    pub fuel_recharge: f32,
+    #[serde(default)]
+    pub wrap_around: bool,
+    #[serde(default)]
+    pub wind_strength: f32,
+    #[serde(default)]
+    pub wind_gap: (f32, f32),
+    #[serde(default)]
+    pub squid_interval: f32,
+    #[serde(default)]
+    pub squid_jitter: f32,
+    #[serde(default)]
+    pub script: Option<String>,
+    /// The events of `script`, filled in by `load_script`. Kept with the difficulty so replays play the script
+    /// they were recorded with, even if its file changes.
+    #[serde(default)]
+    pub waves: Option<WaveScript>,
+    #[serde(default)]
+    pub bands: Vec<AltitudeBand>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AltitudeBand {
+    /// Where the band begins, as a share of the way from the start to the ground.
+    pub start: f32,
+    #[serde(default = "default_tint")]
+    pub tint: (u8, u8, u8),
+    pub bird_interval: f32,
+    #[serde(default = "default_bird_kinds")]
+    pub bird_kinds: Vec<BirdKind>,
+    pub lightning_interval: f32,
+    #[serde(default = "default_lightning_patterns")]
+    pub lightning_patterns: Vec<LightningPattern>,
+    pub cloud_gap: (f32, f32),
+    pub gold_cloud_ratio: f32,
+    #[serde(default)]
+    pub rainbow_ratio: f32,
+    #[serde(default)]
+    pub balloon_ratio: f32,
+}
+
+impl Difficulty {
+    pub fn load_script(&mut self) -> Result<(), String> {
+        if let (None, Some(name)) = (&self.waves, &self.script) {
+            let waves = WaveScript::load(name).map_err(|e| format!("could not load wave script {}: {}", name, e))?;
+            self.waves = Some(waves);
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let check = |ok: bool, message: &str| if ok { Ok(()) } else { Err(message.to_string()) };
+        let positive_gap = |(min, max): (f32, f32)| min > 0.0 && max >= min;
+        check(self.level_depth > 0.0, "level_depth must be positive")?;
+        check(
+            self.max_balloons == 0 || self.max_balloons >= self.balloons,
+            "max_balloons can't be less than balloons",
+        )?;
+        check(self.squid_interval >= 0.0, "squid_interval can't be negative")?;
+        check(
+            self.wind_strength <= 0.0 || positive_gap(self.wind_gap),
+            "wind_gap must be positive and smallest first",
+        )?;
+        for band in std::iter::once(&self.top_band()).chain(&self.bands) {
+            let in_band = |message: &str| format!("{} in the band starting at {}", message, band.start);
+            check(band.bird_interval > 0.0, &in_band("bird_interval must be positive"))?;
+            check(
+                band.lightning_interval > 0.0,
+                &in_band("lightning_interval must be positive"),
+            )?;
+            check(!band.bird_kinds.is_empty(), &in_band("bird_kinds can't be empty"))?;
+            check(
+                !band.lightning_patterns.is_empty(),
+                &in_band("lightning_patterns can't be empty"),
+            )?;
+            check(
+                positive_gap(band.cloud_gap),
+                &in_band("cloud_gap must be positive and smallest first"),
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn top_band(&self) -> AltitudeBand {
+        AltitudeBand {
+            start: 0.0,
+            tint: default_tint(),
+            bird_interval: self.bird_interval,
+            bird_kinds: self.bird_kinds.clone(),
+            lightning_interval: self.lightning_interval,
+            lightning_patterns: self.lightning_patterns.clone(),
+            cloud_gap: self.cloud_gap,
+            gold_cloud_ratio: self.gold_cloud_ratio,
+            rainbow_ratio: self.rainbow_ratio,
+            balloon_ratio: self.balloon_ratio,
+        }
+    }
+}
+
+fn default_tint() -> (u8, u8, u8) {
+    (255, 255, 255)
+}
+
+fn default_bird_kinds() -> Vec<BirdKind> {
+    vec![BirdKind::Basic]
+}
+
+fn default_max_lightning() -> usize {
+    1
+}
+
+fn default_lightning_patterns() -> Vec<LightningPattern> {
+    vec![LightningPattern::Single]
+}
+
+pub fn load_difficulties() -> Vec<Difficulty> {
+    let mut difficulties: Vec<Difficulty> = BUILT_IN
+        .iter()
+        .map(|text| {
+            let mut difficulty: Difficulty = ron::from_str(text).expect("built-in difficulty should be valid");
+            difficulty
+                .load_script()
+                .expect("built-in difficulty should have a valid script");
+            difficulty
+                .validate()
+                .expect("built-in difficulty should have valid settings");
+            difficulty
+        })
+        .collect();
+
+    let mut paths: Vec<_> = match fs::read_dir(DIFFICULTY_DIR) {
+        Ok(entries) => entries.filter_map(|entry| entry.ok().map(|e| e.path())).collect(),
+        Err(_) => return difficulties,
+    };
+    paths.sort();
+    for path in paths
+        .iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+    {
+        match load_difficulty(path) {
+            Ok(difficulty) if difficulties.iter().any(|other| other.name == difficulty.name) => {
+                error!(
+                    "Skipping difficulty {}: there is already one named {}",
+                    path.display(),
+                    difficulty.name
+                )
+            }
+            Ok(difficulty) => difficulties.push(difficulty),
+            Err(e) => error!("Skipping difficulty {}: {}", path.display(), e),
+        }
+    }
+    difficulties
+}
+
+fn load_difficulty(path: &Path) -> Result<Difficulty, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut difficulty: Difficulty = ron::from_str(&text).map_err(|e| e.to_string())?;
+    difficulty.validate()?;
+    difficulty.load_script()?;
+    Ok(difficulty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STORM: &str = include_str!("../assets/difficulties/storm.ron");
+
+    fn storm() -> Difficulty {
+        ron::from_str(STORM).unwrap()
+    }
+
+    #[test]
+    fn built_in_difficulties_are_valid() {
+        let difficulties = load_difficulties();
+        assert!(difficulties.len() >= BUILT_IN.len());
+        for difficulty in &difficulties[..BUILT_IN.len()] {
+            assert!(difficulty.validate().is_ok(), "{} is invalid", difficulty.name);
+        }
+    }
+
+    #[test]
+    fn rejects_settings_the_generator_cant_use() {
+        let broken: [fn(&mut Difficulty); 6] = [
+            |d| d.cloud_gap = (0.0, 100.0),
+            |d| d.bands[0].cloud_gap = (-10.0, 50.0),
+            |d| d.wind_gap = (0.0, 0.0),
+            |d| d.bird_interval = 0.0,
+            |d| d.bands[1].lightning_interval = -1.0,
+            |d| d.max_balloons = d.balloons - 1,
+        ];
+        for (i, breaks) in broken.iter().enumerate() {
+            let mut difficulty = storm();
+            breaks(&mut difficulty);
+            assert!(difficulty.validate().is_err(), "change {} was allowed", i);
+        }
+    }
+
+    #[test]
+    fn max_balloons_can_be_left_out() {
+        let text = STORM
+            .lines()
+            .filter(|line| !line.contains("max_balloons"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let difficulty: Difficulty = ron::from_str(&text).unwrap();
+        assert_eq!(difficulty.max_balloons, 0);
+        assert!(difficulty.validate().is_ok());
+    }
+
+    #[test]
+    fn wind_gap_only_matters_with_wind() {
+        let mut difficulty = storm();
+        difficulty.wind_strength = 0.0;
+        difficulty.wind_gap = (0.0, 0.0);
+        assert!(difficulty.validate().is_ok());
+    }
+}
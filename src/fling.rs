@@ -1,10 +1,28 @@
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::assets::Assets;
+use crate::{
+    assets::Assets,
+    draw_hitbox,
+    player::draw_balloon,
+    wind::{self, WindZone},
+    world::{Viewport, TIME_STEP},
+};
 
+pub const THING_DIM: (f32, f32) = (53.0, 25.0);
+const MAX_FLING_SPEED: f32 = 250.0;
+const MIN_FLING: f32 = 50.0;
+const REST_SPEED: f32 = 20.0;
+const BOUNCINESS: f32 = 0.8;
+const PREVIEW_TIME: f32 = 5.0;
+const PREVIEW_INTERVAL: f32 = 0.25;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum FlingKind {
     Cloud,
     GoldCloud,
+    Rainbow,
+    Balloon,
 }
 
 enum State {
@@ -13,9 +31,91 @@ enum State {
     Destroyed,
 }
 
+/// How flung things fly. The simulation and the cursor's trajectory preview both move things through here,
+/// so the preview always matches.
+#[derive(Clone, Copy)]
+pub struct Physics {
+    pub drag: f32,
+    pub gravity: f32,
+}
+
+impl Physics {
+    pub fn step(
+        &self,
+        position: &mut Vec2,
+        velocity: &mut Vec2,
+        wind_zones: &[WindZone],
+        viewport: Viewport,
+        elapsed: f32,
+    ) -> f32 {
+        let wind = wind::force_at(wind_zones, position.y + THING_DIM.1 / 2.0);
+        *velocity += (wind + vec2(0.0, self.gravity)) * elapsed;
+        *velocity *= f32::max(1.0 - self.drag * elapsed, 0.0);
+        *position += *velocity * elapsed;
+
+        let max_x = viewport.width - THING_DIM.0;
+        if viewport.wrap {
+            let shift = viewport.wrap_shift(position.x, THING_DIM.0);
+            position.x += shift;
+            return shift;
+        } else if position.x < 0.0 {
+            position.x = -position.x;
+            velocity.x = velocity.x.abs() * BOUNCINESS;
+        } else if position.x > max_x {
+            position.x = 2.0 * max_x - position.x;
+            velocity.x = -velocity.x.abs() * BOUNCINESS;
+        }
+        0.0
+    }
+
+    pub fn trajectory(&self, start: Vec2, velocity: Vec2, wind_zones: &[WindZone], viewport: Viewport) -> Vec<Vec2> {
+        let (mut position, mut velocity) = (start, velocity);
+        let mut points = Vec::new();
+        let steps_per_point = (PREVIEW_INTERVAL / TIME_STEP).round() as usize;
+        for step in 1..=(PREVIEW_TIME / TIME_STEP).round() as usize {
+            if velocity.length() < REST_SPEED {
+                break;
+            }
+            self.step(&mut position, &mut velocity, wind_zones, viewport, TIME_STEP);
+            if step % steps_per_point == 0 {
+                points.push(position);
+            }
+        }
+        points
+    }
+}
+
+pub fn bounce(a: &mut FlingThing, b: &mut FlingThing, viewport: Viewport) {
+    let mut offset = b.center() - a.center();
+    if viewport.wrap && offset.x.abs() > viewport.width / 2.0 {
+        // Touching across the sides of the screen
+        offset.x -= viewport.width * offset.x.signum();
+    }
+    let normal = offset.normalize_or_zero();
+    let closing = (a.velocity - b.velocity).dot(normal);
+    if closing <= 0.0 {
+        // Already moving apart
+        return;
+    }
+    a.knock(a.velocity - normal * closing);
+    b.knock(b.velocity + normal * closing);
+}
+
+pub fn launch_velocity(pull: Vec2) -> Option<Vec2> {
+    if pull.x.abs() > MIN_FLING || pull.y.abs() > MIN_FLING {
+        Some(pull.clamp(
+            vec2(-MAX_FLING_SPEED, -MAX_FLING_SPEED),
+            vec2(MAX_FLING_SPEED, MAX_FLING_SPEED),
+        ))
+    } else {
+        None
+    }
+}
+
 pub struct FlingThing {
     pub kind: FlingKind,
     pub position: Vec2,
+    last_position: Vec2,
     pub velocity: Vec2,
     state: State,
     pub hitbox: Rect,
@@ -26,6 +126,7 @@ impl FlingThing {
         let mut thing = Self {
             kind,
             position,
+            last_position: position,
             velocity: Vec2::default(),
             state: State::Normal,
             hitbox: Rect::new(position.x, position.y, 48.0, 22.0),
@@ -34,45 +135,67 @@ impl FlingThing {
         thing
     }
 
-    pub fn fling(&mut self, velocity: Vec2) {
-        if !self.flung() && (velocity.x.abs() > 50.0 || velocity.y.abs() > 50.0) {
+    pub fn fling(&mut self, pull: Vec2) {
+        if let (false, Some(velocity)) = (self.flung(), launch_velocity(pull)) {
             self.velocity = velocity;
-            self.velocity.x = f32::min(self.velocity.x, 250.0);
-            self.velocity.x = f32::max(self.velocity.x, -250.0);
-            self.velocity.y = f32::min(self.velocity.y, 250.0);
-            self.velocity.y = f32::max(self.velocity.y, -250.0);
             self.state = State::Flung;
         }
     }
 
-    pub fn update(&mut self, elapsed: f32) {
+    pub fn update(&mut self, physics: &Physics, wind_zones: &[WindZone], viewport: Viewport, elapsed: f32) {
+        self.last_position = self.position;
         if let State::Flung = self.state {
-            self.position += self.velocity * elapsed;
+            let shift = physics.step(&mut self.position, &mut self.velocity, wind_zones, viewport, elapsed);
+            self.last_position.x += shift;
+            if self.velocity.length() < REST_SPEED {
+                self.velocity = Vec2::default();
+                self.state = State::Normal;
+            }
             self.update_hitbox();
         }
     }
 
-    pub fn draw(&self, assets: &Assets) {
+    pub fn draw(&self, assets: &Assets, alpha: f32) {
         if matches!(self.state, State::Destroyed) {
             return;
         }
-        let texture = match self.kind {
-            FlingKind::Cloud => assets.cloud,
-            FlingKind::GoldCloud => assets.gold_cloud,
-        };
-        draw_texture(texture, self.position.x, self.position.y, WHITE);
-        // draw_rectangle(self.hitbox.x, self.hitbox.y, self.hitbox.w, self.hitbox.h, Color::from_rgba(255, 0, 0, 128));
+        let position = self.last_position.lerp(self.position, alpha);
+        match self.kind {
+            FlingKind::Cloud => draw_texture(assets.cloud, position.x, position.y, WHITE),
+            FlingKind::GoldCloud => draw_texture(assets.gold_cloud, position.x, position.y, WHITE),
+            FlingKind::Rainbow => draw_rainbow(position + vec2(26.5, 22.0), 24.0, 1.0),
+            FlingKind::Balloon => {
+                // Bob gently so it reads as floating rather than as part of the scenery
+                let center = position + vec2(26.5, 6.0 + f32::sin(position.y * 0.1 + get_time() as f32 * 3.0) * 2.0);
+                draw_balloon(center, center + vec2(0.0, 22.0), Color::from_rgba(230, 70, 80, 255));
+            }
+        }
+        draw_hitbox(&self.hitbox);
     }
 
     fn update_hitbox(&mut self) {
-        self.hitbox.x = self.position.x + (53.0 - self.hitbox.w) / 2.0;
-        self.hitbox.y = self.position.y + (25.0 - self.hitbox.h) / 2.0;
+        self.hitbox.x = self.position.x + (THING_DIM.0 - self.hitbox.w) / 2.0;
+        self.hitbox.y = self.position.y + (THING_DIM.1 - self.hitbox.h) / 2.0;
     }
 
     pub fn flung(&self) -> bool {
         matches!(self.state, State::Flung)
     }
 
+    fn knock(&mut self, velocity: Vec2) {
+        if velocity.length() < REST_SPEED {
+            self.velocity = Vec2::default();
+            self.state = State::Normal;
+        } else {
+            self.velocity = velocity;
+            self.state = State::Flung;
+        }
+    }
+
+    fn center(&self) -> Vec2 {
+        vec2(self.hitbox.x + self.hitbox.w / 2.0, self.hitbox.y + self.hitbox.h / 2.0)
+    }
+
     pub fn collision(&mut self) {
         self.state = State::Destroyed;
         self.hitbox = Rect::default();
@@ -82,3 +205,24 @@ impl FlingThing {
         matches!(self.state, State::Destroyed)
     }
 }
+
+pub fn draw_rainbow(center: Vec2, radius: f32, alpha: f32) {
+    const SEGMENTS: usize = 16;
+    for band in 0..6 {
+        let mut color = macroquad::color::hsl_to_rgb(band as f32 / 7.0, 0.9, 0.6);
+        color.a = alpha;
+        let band_radius = radius - band as f32 * 3.0;
+        for i in 0..SEGMENTS {
+            let from = std::f32::consts::PI * (1.0 + i as f32 / SEGMENTS as f32);
+            let to = std::f32::consts::PI * (1.0 + (i + 1) as f32 / SEGMENTS as f32);
+            draw_line(
+                center.x + from.cos() * band_radius,
+                center.y + from.sin() * band_radius,
+                center.x + to.cos() * band_radius,
+                center.y + to.sin() * band_radius,
+                3.0,
+                color,
+            );
+        }
+    }
+}
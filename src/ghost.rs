@@ -0,0 +1,93 @@
+use std::{fs, io::ErrorKind, path::PathBuf};
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{assets::Assets, player::Player};
+
+pub const GHOST_DIR: &str = "ghosts";
+const SAMPLE_INTERVAL: f32 = 0.05;
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+struct Sample {
+    time: f32,
+    position: (f32, f32),
+    balloons: usize,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct Ghost {
+    pub time: f32,
+    samples: Vec<Sample>,
+}
+
+impl Ghost {
+    pub fn record(&mut self, time: f32, player: &Player) {
+        if self
+            .samples
+            .last()
+            .is_some_and(|last| time < last.time + SAMPLE_INTERVAL)
+        {
+            return;
+        }
+        self.time = time;
+        self.samples.push(Sample {
+            time,
+            position: (player.position.x, player.position.y),
+            balloons: player.balloons(),
+        });
+    }
+
+    fn sample(&self, time: f32) -> Option<(Vec2, usize)> {
+        let next = self.samples.iter().position(|sample| sample.time > time);
+        let (from, to) = match next {
+            Some(0) => (self.samples[0], self.samples[0]),
+            Some(i) => (self.samples[i - 1], self.samples[i]),
+            None => (*self.samples.last()?, *self.samples.last()?),
+        };
+        let t = if to.time > from.time {
+            (time - from.time) / (to.time - from.time)
+        } else {
+            0.0
+        };
+        let from_position = vec2(from.position.0, from.position.1);
+        let to_position = vec2(to.position.0, to.position.1);
+        Some((from_position.lerp(to_position, t), from.balloons))
+    }
+
+    pub fn draw(&self, time: f32, assets: &Assets) {
+        if let Some((position, balloons)) = self.sample(time) {
+            Player::draw_body(balloons, position, Color::from_rgba(255, 255, 255, 90), assets);
+        }
+    }
+
+    fn path(difficulty: &str, seed: u64) -> PathBuf {
+        let difficulty: String = difficulty
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '_' })
+            .collect();
+        PathBuf::from(GHOST_DIR).join(format!("{}-{}.ron", difficulty, seed))
+    }
+
+    pub fn load(difficulty: &str, seed: u64) -> Option<Self> {
+        let path = Self::path(difficulty, seed);
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => return None,
+            Err(e) => {
+                error!("Could not read ghost {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        ron::from_str(&text)
+            .map_err(|e| error!("Ignoring invalid ghost {}: {}", path.display(), e))
+            .ok()
+    }
+
+    pub fn save(&self, difficulty: &str, seed: u64) -> Result<(), String> {
+        let path = Self::path(difficulty, seed);
+        let text = ron::to_string(self).map_err(|e| e.to_string())?;
+        fs::create_dir_all(GHOST_DIR).map_err(|e| format!("could not create {}: {}", GHOST_DIR, e))?;
+        fs::write(&path, text).map_err(|e| format!("could not write ghost {}: {}", path.display(), e))
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::OnceLock;
+
+use macroquad::{audio::Sound, prelude::*};
+use serde::Deserialize;
+
+use crate::assets::Assets;
+
+static LANDINGS: OnceLock<Vec<Landing>> = OnceLock::new();
+/// How far down the landing sprites the ground surface is. Craters and puddles dip below it.
+const SPRITE_GROUND: f32 = 128.0;
+
+#[derive(Clone, Copy, Deserialize)]
+pub enum LandingSprite {
+    Crater,
+    Puddle,
+    Haystack,
+    Flowers,
+    Trampoline,
+    Crowd,
+    Parade,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+pub enum LandingSound {
+    Crash,
+    Splash,
+    Rustle,
+    Chime,
+    Boing,
+    Cheer,
+    Fanfare,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Landing {
+    pub min_time: f32,
+    #[serde(default)]
+    pub min_balloons: usize,
+    pub sprite: LandingSprite,
+    pub sound: LandingSound,
+    pub message: String,
+}
+
+pub fn landings() -> &'static [Landing] {
+    LANDINGS.get_or_init(|| {
+        ron::from_str(include_str!("../assets/landings.ron")).expect("built-in landings should be valid")
+    })
+}
+
+/// The best landing the run qualifies for, which is the last one listed.
+pub fn choose(time: f32, balloons: usize) -> &'static Landing {
+    let landings = landings();
+    landings
+        .iter()
+        .rev()
+        .find(|landing| time >= landing.min_time && balloons >= landing.min_balloons)
+        .unwrap_or(&landings[0])
+}
+
+impl LandingSound {
+    pub fn source(self, assets: &Assets) -> Sound {
+        match self {
+            LandingSound::Crash => assets.sfx_crash,
+            LandingSound::Splash => assets.sfx_splash,
+            LandingSound::Rustle => assets.sfx_rustle,
+            LandingSound::Chime => assets.sfx_chime,
+            LandingSound::Boing => assets.sfx_boing,
+            LandingSound::Cheer => assets.sfx_cheer,
+            LandingSound::Fanfare => assets.sfx_fanfare,
+        }
+    }
+}
+
+impl LandingSprite {
+    pub fn texture(self, assets: &Assets) -> Texture2D {
+        match self {
+            LandingSprite::Crater => assets.landing_crater,
+            LandingSprite::Puddle => assets.landing_puddle,
+            LandingSprite::Haystack => assets.landing_haystack,
+            LandingSprite::Flowers => assets.landing_flowers,
+            LandingSprite::Trampoline => assets.landing_trampoline,
+            LandingSprite::Crowd => assets.landing_crowd,
+            LandingSprite::Parade => assets.landing_parade,
+        }
+    }
+}
+
+impl Landing {
+    pub fn draw(&self, x: f32, ground_y: f32, assets: &Assets) {
+        let texture = self.sprite.texture(assets);
+        draw_texture(texture, x - texture.width() / 2.0, ground_y - SPRITE_GROUND, WHITE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_best_landing_reached() {
+        assert_eq!(choose(10.0, 5).message, landings()[0].message);
+        assert_eq!(choose(65.0, 1).message, "Eh... Good enough");
+        assert_eq!(choose(65.0, 3).message, "Gentle as a petal");
+        assert_eq!(choose(f32::MAX, usize::MAX).message, landings().last().unwrap().message);
+    }
+}
@@ -1,85 +1,479 @@
-use macroquad::{
-    prelude::*,
-    rand::{gen_range, rand},
-};
+use macroquad::prelude::*;
 
 use crate::{
-    bird::Bird,
+    assets::Assets,
+    bird::{Bird, BirdKind, BIRD_SIZE},
+    difficulty::{AltitudeBand, Difficulty},
     fling::{FlingKind, FlingThing},
-    lightning::{Lightning, LIGHTING_CLOUD_DIM},
+    lightning::{Lightning, LightningPattern, Pattern, BOLT_WIDTH, LIGHTING_CLOUD_DIM},
     player::{Player, PLAYER_DIM},
+    rng::Rng,
+    squid::{Squid, SQUID_DIM},
+    wave::{Trigger, WaveEvent, WaveScript, WaveSpawn},
+    wind::WindZone,
+    world::Viewport,
     HDirection,
 };
 
-pub enum Difficulty {
-    Normal,
-    Hard,
+const SPAWN_WARNING: f32 = 1.5;
+const SWEEP_SPEED: f32 = 60.0;
+const TINT_BLEND: f32 = 300.0;
+const WARNING_FONT_SIZE: u16 = 20;
+
+pub enum SpawnKind {
+    Bird {
+        offset: f32,
+        dir: HDirection,
+        kind: BirdKind,
+    },
+    Lightning {
+        x: f32,
+        pattern: Pattern,
+    },
+}
+
+pub struct Spawn {
+    pub kind: SpawnKind,
+    pub time_left: f32,
+}
+
+pub struct Bands {
+    level_depth: f32,
+    bands: Vec<AltitudeBand>,
+}
+
+impl Bands {
+    pub fn new(difficulty: &Difficulty) -> Self {
+        let mut bands = vec![difficulty.top_band()];
+        bands.extend(difficulty.bands.iter().cloned());
+        bands.sort_by(|a, b| a.start.total_cmp(&b.start));
+        Self {
+            level_depth: difficulty.level_depth,
+            bands,
+        }
+    }
+
+    fn index_at(&self, y: f32) -> usize {
+        self.bands
+            .iter()
+            .rposition(|band| band.start * self.level_depth <= y)
+            .unwrap_or(0)
+    }
+
+    pub fn at(&self, y: f32) -> &AltitudeBand {
+        &self.bands[self.index_at(y)]
+    }
+
+    pub fn tint_at(&self, y: f32) -> Color {
+        let index = self.index_at(y);
+        let band = &self.bands[index];
+        let color = |(r, g, b): (u8, u8, u8)| vec3(r as f32, g as f32, b as f32) / 255.0;
+        let tint = match index {
+            0 => color(band.tint),
+            _ => {
+                let blend = ((y - band.start * self.level_depth) / TINT_BLEND).clamp(0.0, 1.0);
+                color(self.bands[index - 1].tint).lerp(color(band.tint), blend)
+            }
+        };
+        Color::new(tint.x, tint.y, tint.z, 1.0)
+    }
 }
 
 pub struct Generator {
+    pending: Vec<Spawn>,
+    bands: Bands,
+    events: Vec<WaveEvent>,
+    random_hazards: bool,
+    time: f32,
     bird_timer: f32,
     lightning_timer: f32,
-    time_between_birds: f32,
-    time_between_lightning: f32,
+    squid_timer: f32,
+    time_between_squids: f32,
+    bird_jitter: f32,
+    lightning_jitter: f32,
+    squid_jitter: f32,
+    max_lightning: usize,
 }
 
 impl Generator {
-    pub fn new(difficulty: Difficulty) -> Self {
-        let (time_between_birds, time_between_lightning) = match difficulty {
-            Difficulty::Normal => (6.0, 12.0),
-            Difficulty::Hard => (3.0, 6.0),
+    pub fn new(difficulty: &Difficulty, script: Option<WaveScript>) -> Self {
+        let (events, random_hazards) = match script {
+            Some(script) => (script.events, script.random_hazards),
+            None => (Vec::new(), true),
         };
         Self {
-            bird_timer: time_between_birds,
-            lightning_timer: time_between_lightning,
-            time_between_birds,
-            time_between_lightning,
+            pending: Vec::new(),
+            bands: Bands::new(difficulty),
+            events,
+            random_hazards,
+            time: 0.0,
+            bird_timer: difficulty.bird_interval - SPAWN_WARNING,
+            lightning_timer: difficulty.lightning_interval - SPAWN_WARNING,
+            squid_timer: difficulty.squid_interval,
+            time_between_squids: difficulty.squid_interval,
+            bird_jitter: difficulty.bird_jitter,
+            lightning_jitter: difficulty.lightning_jitter,
+            squid_jitter: difficulty.squid_jitter,
+            max_lightning: difficulty.max_lightning,
         }
     }
 
+    pub fn skip_to(&mut self, depth: f32) {
+        self.events
+            .retain(|event| !matches!(event.at, Trigger::Depth(at) if at < depth));
+    }
+
+    pub fn bands(&self) -> &Bands {
+        &self.bands
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub fn generate(
         &mut self,
-        camera: &Camera2D,
+        camera_y: f32,
+        viewport: Viewport,
         player: &Player,
         birds: &mut Vec<Bird>,
-        lightning: &mut Option<Lightning>,
+        lightning: &mut Vec<Lightning>,
+        squids: &mut Vec<Squid>,
+        fling_things: &mut Vec<FlingThing>,
+        rng: &mut Rng,
         elapsed: f32,
     ) {
+        self.time += elapsed;
+        self.run_script(viewport, player, fling_things, rng);
+
         self.bird_timer -= elapsed;
         self.lightning_timer -= elapsed;
+        self.squid_timer -= elapsed;
+        let band = self.bands.at(player.position.y);
 
-        if self.bird_timer < 0.0 {
-            let y_pos = gen_range(player.position.y + 20.0, camera.target.y + screen_width() / 2.0);
-            let move_dir = match rand() % 2 {
+        if self.random_hazards && self.bird_timer < 0.0 {
+            let mut offset = rng.gen_range(player.position.y + 20.0 - camera_y, viewport.width / 2.0);
+            let dir = match rng.rand() % 2 {
                 0 => HDirection::Left,
                 _ => HDirection::Right,
             };
-            birds.push(Bird::spawn(y_pos, move_dir));
-            self.bird_timer = self.time_between_birds + gen_range(-1.0, 1.0);
+            let kind = match band.bird_kinds.len() {
+                0 => BirdKind::Basic,
+                n => band.bird_kinds[rng.rand() as usize % n],
+            };
+            if kind == BirdKind::Diver {
+                // Divers come in just under the top bar
+                offset = 60.0 - viewport.height / 2.0;
+            }
+            self.bird_timer = band.bird_interval + rng.gen_range(-self.bird_jitter, self.bird_jitter);
+            self.schedule(SpawnKind::Bird { offset, dir, kind });
+        }
+
+        let lightning_pending = self
+            .pending
+            .iter()
+            .filter(|spawn| matches!(spawn.kind, SpawnKind::Lightning { .. }))
+            .count();
+        let room = self.max_lightning.saturating_sub(lightning.len() + lightning_pending);
+        if self.random_hazards && self.lightning_timer < 0.0 && room > 0 {
+            let band = self.bands.at(player.position.y);
+            self.lightning_timer =
+                band.lightning_interval + rng.gen_range(-self.lightning_jitter, self.lightning_jitter);
+            self.schedule_lightning(player, viewport, room, rng);
         }
 
-        if self.lightning_timer < 0.0 && lightning.is_none() {
-            let x_pos = player.position.x + (PLAYER_DIM.0 - LIGHTING_CLOUD_DIM.0) / 2.0;
-            let y_pos = camera.target.y - screen_height() / 2.0 - 100.0;
-            *lightning = Some(Lightning::new(vec2(x_pos, y_pos)));
-            self.lightning_timer = self.time_between_lightning + gen_range(-2.0, 2.0);
+        for spawn in self.pending.iter_mut() {
+            spawn.time_left -= elapsed;
+            if spawn.time_left >= 0.0 {
+                continue;
+            }
+            match spawn.kind {
+                SpawnKind::Bird {
+                    offset,
+                    dir,
+                    kind: BirdKind::Flock,
+                } => birds.extend(Bird::flock(camera_y + offset, dir, viewport)),
+                SpawnKind::Bird { offset, dir, kind } => {
+                    birds.push(Bird::spawn(kind, camera_y + offset, dir, viewport))
+                }
+                SpawnKind::Lightning { x, pattern } => {
+                    let y_pos = camera_y - viewport.height / 2.0 - 100.0;
+                    lightning.push(Lightning::new(vec2(x, y_pos), pattern));
+                }
+            }
         }
+        self.pending.retain(|spawn| spawn.time_left >= 0.0);
+
+        if self.random_hazards && self.time_between_squids > 0.0 && self.squid_timer < 0.0 {
+            // Rises into view from below, so the player falls towards it
+            let x_pos = rng.gen_range(0.0, viewport.width - SQUID_DIM.0);
+            let y_pos = camera_y + viewport.height / 2.0 + 20.0;
+            squids.push(Squid::spawn(x_pos, y_pos));
+            self.squid_timer = self.time_between_squids + rng.gen_range(-self.squid_jitter, self.squid_jitter);
+        }
+    }
+
+    fn run_script(&mut self, viewport: Viewport, player: &Player, fling_things: &mut Vec<FlingThing>, rng: &mut Rng) {
+        let (due, waiting): (Vec<_>, Vec<_>) = self
+            .events
+            .iter()
+            .partition(|event| event.at.reached(self.time, player.position.y));
+        self.events = waiting;
+        for event in due {
+            match event.spawn {
+                WaveSpawn::Bird { side, height, kind } => {
+                    // Entering from the left means flying right
+                    let dir = match side {
+                        HDirection::Left => HDirection::Right,
+                        HDirection::Right => HDirection::Left,
+                    };
+                    let offset = height - viewport.height / 2.0;
+                    self.schedule(SpawnKind::Bird { offset, dir, kind });
+                }
+                WaveSpawn::Lightning { offset, pattern } => {
+                    let x = player.position.x + (PLAYER_DIM.0 - LIGHTING_CLOUD_DIM.0) / 2.0 + offset;
+                    self.schedule_pattern(pattern, x, viewport, rng);
+                }
+                WaveSpawn::Cloud { kind, x, y } => fling_things.push(FlingThing::new(kind, vec2(x, y))),
+            }
+        }
+    }
+
+    /// Picks one of the current band's thunder cloud patterns, aimed at the player. Falls back to a single cloud
+    /// when the pattern needs more than `room` clouds.
+    fn schedule_lightning(&mut self, player: &Player, viewport: Viewport, room: usize, rng: &mut Rng) {
+        let x = player.position.x + (PLAYER_DIM.0 - LIGHTING_CLOUD_DIM.0) / 2.0;
+        let patterns = &self.bands.at(player.position.y).lightning_patterns;
+        let kind = match patterns.len() {
+            0 => LightningPattern::Single,
+            n => patterns[rng.rand() as usize % n],
+        };
+        let kind = if kind.clouds() > room {
+            LightningPattern::Single
+        } else {
+            kind
+        };
+        self.schedule_pattern(kind, x, viewport, rng);
+    }
+
+    fn schedule_pattern(&mut self, kind: LightningPattern, x: f32, viewport: Viewport, rng: &mut Rng) {
+        match kind {
+            LightningPattern::Single => self.schedule(SpawnKind::Lightning {
+                x,
+                pattern: Pattern::Single,
+            }),
+            LightningPattern::Sweep => {
+                let speed = match rng.rand() % 2 {
+                    0 => -SWEEP_SPEED,
+                    _ => SWEEP_SPEED,
+                };
+                // Start off to one side so that it passes over the player halfway through charging
+                let x = (x - speed * 2.5).clamp(0.0, viewport.width - LIGHTING_CLOUD_DIM.0);
+                self.schedule(SpawnKind::Lightning {
+                    x,
+                    pattern: Pattern::Sweep { speed },
+                });
+            }
+            LightningPattern::Double => self.schedule(SpawnKind::Lightning {
+                x,
+                pattern: Pattern::Double { strikes_left: 2 },
+            }),
+            LightningPattern::Bracket => {
+                let offset = (BOLT_WIDTH + PLAYER_DIM.0) / 2.0 + 20.0;
+                self.schedule(SpawnKind::Lightning {
+                    x: x - offset,
+                    pattern: Pattern::Bracket {
+                        side: HDirection::Right,
+                    },
+                });
+                self.schedule(SpawnKind::Lightning {
+                    x: x + offset,
+                    pattern: Pattern::Bracket { side: HDirection::Left },
+                });
+            }
+            LightningPattern::Predictive => self.schedule(SpawnKind::Lightning {
+                x,
+                pattern: Pattern::Predictive { target: None },
+            }),
+        }
+    }
+
+    fn schedule(&mut self, kind: SpawnKind) {
+        self.pending.push(Spawn {
+            kind,
+            time_left: SPAWN_WARNING,
+        });
+    }
+
+    pub fn upcoming(&self) -> &[Spawn] {
+        &self.pending
     }
 }
 
-pub fn generate_fling_things(ground_position: f32) -> Vec<FlingThing> {
+impl Spawn {
+    pub fn draw_warning(&self, camera_y: f32, viewport: Viewport, assets: &Assets) {
+        let top = camera_y - viewport.height / 2.0;
+        let color = Color::from_rgba(196, 84, 91, 255);
+        let label = format!("{:.1}s", self.time_left);
+        let label_width = measure_text(&label, Some(assets.font), WARNING_FONT_SIZE, 1.0).width;
+        match self.kind {
+            SpawnKind::Bird { offset, dir, .. } => {
+                let y = camera_y + offset + BIRD_SIZE / 2.0;
+                let (tip, back, text_x) = match dir {
+                    // Flying left means entering from the right edge
+                    HDirection::Left => (
+                        viewport.width - 4.0,
+                        viewport.width - 20.0,
+                        viewport.width - 24.0 - label_width,
+                    ),
+                    HDirection::Right => (4.0, 20.0, 24.0),
+                };
+                draw_triangle(vec2(tip, y), vec2(back, y - 10.0), vec2(back, y + 10.0), color);
+                draw_warning_text(&label, text_x, y + 7.0, assets);
+            }
+            SpawnKind::Lightning { x, .. } => {
+                let x = x + LIGHTING_CLOUD_DIM.0 / 2.0;
+                let y = top + 44.0;
+                draw_triangle(vec2(x, y + 16.0), vec2(x - 10.0, y), vec2(x + 10.0, y), color);
+                // Keep the label on screen for clouds near the right edge
+                let text_x = if x + 14.0 + label_width > viewport.width {
+                    x - 14.0 - label_width
+                } else {
+                    x + 14.0
+                };
+                draw_warning_text(&label, text_x, y + 16.0, assets);
+            }
+        }
+    }
+}
+
+fn draw_warning_text(text: &str, x: f32, y: f32, assets: &Assets) {
+    draw_text_ex(
+        text,
+        x,
+        y,
+        TextParams {
+            font: assets.font,
+            font_size: WARNING_FONT_SIZE,
+            ..Default::default()
+        },
+    );
+}
+
+pub fn generate_fling_things(difficulty: &Difficulty, viewport: Viewport, rng: &mut Rng) -> Vec<FlingThing> {
     let mut things = Vec::new();
+    let bands = Bands::new(difficulty);
 
     let mut y_pos = 50.0;
-    while y_pos < ground_position - 200.0 {
-        let x_pos = gen_range(10.0, screen_width() - 10.0);
-        y_pos += gen_range(50.0, 150.0);
-        let kind = match rand() % 5 {
-            0 => FlingKind::GoldCloud,
-            _ => FlingKind::Cloud,
+    while y_pos < difficulty.level_depth - 200.0 {
+        let band = bands.at(y_pos);
+        let x_pos = rng.gen_range(10.0, viewport.width - 10.0);
+        y_pos += rng.gen_range(band.cloud_gap.0, band.cloud_gap.1);
+        let roll = rng.gen_range(0.0, 1.0);
+        let kind = if roll < band.gold_cloud_ratio {
+            FlingKind::GoldCloud
+        } else if roll < band.gold_cloud_ratio + band.rainbow_ratio {
+            FlingKind::Rainbow
+        } else if roll < band.gold_cloud_ratio + band.rainbow_ratio + band.balloon_ratio {
+            FlingKind::Balloon
+        } else {
+            FlingKind::Cloud
         };
         things.push(FlingThing::new(kind, vec2(x_pos, y_pos)));
     }
 
     things
 }
+
+pub fn generate_wind_zones(difficulty: &Difficulty, viewport: Viewport, rng: &mut Rng) -> Vec<WindZone> {
+    let mut zones = Vec::new();
+    if difficulty.wind_strength <= 0.0 {
+        return zones;
+    }
+
+    let mut y_pos = viewport.height;
+    loop {
+        y_pos += rng.gen_range(difficulty.wind_gap.0, difficulty.wind_gap.1);
+        let height = rng.gen_range(150.0, 350.0);
+        if y_pos + height > difficulty.level_depth - 200.0 {
+            break;
+        }
+        let direction = match rng.rand() % 4 {
+            0 => vec2(-1.0, 0.0),
+            1 => vec2(1.0, 0.0),
+            2 => vec2(0.0, -1.0),
+            _ => vec2(0.0, 1.0),
+        };
+        let strength = rng.gen_range(0.5, 1.0) * difficulty.wind_strength;
+        zones.push(WindZone::new(y_pos, height, direction * strength));
+        y_pos += height;
+    }
+
+    zones
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::load_difficulties;
+
+    fn event(at: Trigger) -> WaveEvent {
+        WaveEvent {
+            at,
+            spawn: WaveSpawn::Lightning {
+                offset: 0.0,
+                pattern: LightningPattern::Single,
+            },
+        }
+    }
+
+    #[test]
+    fn patterns_stay_under_max_lightning() {
+        let difficulty = Difficulty {
+            max_lightning: 1,
+            lightning_interval: 0.5,
+            lightning_patterns: vec![LightningPattern::Bracket],
+            bands: Vec::new(),
+            ..load_difficulties().remove(0)
+        };
+        let viewport = Viewport {
+            width: 400.0,
+            height: 640.0,
+            wrap: false,
+        };
+        let player = Player::new(vec2(200.0, 300.0), 3, 3, 1.0, 1.0);
+        let mut generator = Generator::new(&difficulty, None);
+        let (mut birds, mut lightning, mut squids, mut fling_things) = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        let mut rng = Rng::new(3);
+        for _ in 0..120 * 10 {
+            generator.generate(
+                320.0,
+                viewport,
+                &player,
+                &mut birds,
+                &mut lightning,
+                &mut squids,
+                &mut fling_things,
+                &mut rng,
+                1.0 / 120.0,
+            );
+            assert!(lightning.len() <= 1, "{} thunder clouds", lightning.len());
+        }
+        assert_eq!(lightning.len(), 1);
+    }
+
+    #[test]
+    fn skip_to_drops_only_depths_above() {
+        let script = WaveScript {
+            random_hazards: false,
+            random_clouds: true,
+            level_depth: None,
+            events: vec![
+                event(Trigger::Depth(100.0)),
+                event(Trigger::Time(1.0)),
+                event(Trigger::Depth(500.0)),
+            ],
+        };
+        let mut generator = Generator::new(&load_difficulties().remove(0), Some(script));
+        generator.skip_to(300.0);
+        let left: Vec<_> = generator.events.iter().map(|event| event.at).collect();
+        assert!(matches!(left[..], [Trigger::Time(t), Trigger::Depth(d)] if t == 1.0 && d == 500.0));
+    }
+}
@@ -1,13 +1,50 @@
 use enum_map::EnumMap;
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
-use crate::{assets::Assets, scenes::game_scene::Sound};
+use crate::{
+    assets::Assets,
+    draw_hitbox,
+    player::{Player, PLAYER_DIM},
+    world::{Sound, Viewport},
+    HDirection,
+};
 
 pub const LIGHTING_CLOUD_DIM: (f32, f32) = (192.0, 96.0);
+pub const BOLT_WIDTH: f32 = 50.0;
 const LIGHTNING_SPEED: f32 = 120.0;
 const LIGHTNING_TIMER: f32 = 5.0;
 const BOLT_TIMER: f32 = 0.4;
-const BOLT_WIDTH: f32 = 50.0;
+const TELEGRAPH_TIME: f32 = 1.5;
+const DOUBLE_STRIKE_GAP: f32 = 0.8;
+const PREDICTIVE_SPEED: f32 = 150.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LightningPattern {
+    Single,
+    Sweep,
+    Double,
+    Bracket,
+    Predictive,
+}
+
+impl LightningPattern {
+    pub fn clouds(self) -> usize {
+        match self {
+            LightningPattern::Bracket => 2,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Pattern {
+    Single,
+    Sweep { speed: f32 },
+    Double { strikes_left: usize },
+    Bracket { side: HDirection },
+    Predictive { target: Option<Vec2> },
+}
 
 #[derive(Debug)]
 enum State {
@@ -19,16 +56,20 @@ enum State {
 
 pub struct Lightning {
     state: State,
+    pattern: Pattern,
     position: Vec2,
+    last_position: Vec2,
     timer: f32,
     cloud_hitbox: Rect,
 }
 
 impl Lightning {
-    pub fn new(position: Vec2) -> Lightning {
+    pub fn new(position: Vec2, pattern: Pattern) -> Lightning {
         Self {
             state: State::Appearing,
+            pattern,
             position,
+            last_position: position,
             timer: LIGHTNING_TIMER,
             cloud_hitbox: Rect::new(
                 position.x,
@@ -39,16 +80,25 @@ impl Lightning {
         }
     }
 
-    pub fn update(&mut self, camera: &Camera2D, sounds: &mut EnumMap<Sound, bool>, elapsed: f32) {
+    pub fn update(
+        &mut self,
+        camera_y: f32,
+        viewport: Viewport,
+        player: &Player,
+        sounds: &mut EnumMap<Sound, bool>,
+        elapsed: f32,
+    ) {
+        self.last_position = self.position;
         match self.state {
             State::Appearing => {
-                if self.position.y < camera.target.y - screen_height() / 2.0 {
+                if self.position.y < camera_y - viewport.height / 2.0 {
                     self.position.y += LIGHTNING_SPEED * elapsed;
                 } else {
                     self.state = State::Waiting;
                 }
             }
             State::Waiting => {
+                self.charge(viewport, player, elapsed);
                 if self.timer < 0.0 {
                     sounds[Sound::Lightning] = true;
                     self.state = State::Striking {
@@ -56,7 +106,7 @@ impl Lightning {
                             self.position.x + (LIGHTING_CLOUD_DIM.0 - BOLT_WIDTH) / 2.0,
                             self.position.y,
                             BOLT_WIDTH,
-                            screen_height() * 2.0,
+                            viewport.height * 2.0,
                         ),
                     };
                     self.timer = BOLT_TIMER;
@@ -64,8 +114,17 @@ impl Lightning {
             }
             State::Striking { .. } => {
                 if self.timer < 0.0 {
-                    self.state = State::Destroyed;
-                    self.cloud_hitbox = Rect::default();
+                    match self.pattern {
+                        Pattern::Double { ref mut strikes_left } if *strikes_left > 1 => {
+                            *strikes_left -= 1;
+                            self.state = State::Waiting;
+                            self.timer = DOUBLE_STRIKE_GAP;
+                        }
+                        _ => {
+                            self.state = State::Destroyed;
+                            self.cloud_hitbox = Rect::default();
+                        }
+                    }
                 }
             }
             State::Destroyed => {}
@@ -74,10 +133,42 @@ impl Lightning {
         self.timer -= elapsed;
     }
 
-    pub fn draw(&self, assets: &Assets) {
+    fn charge(&mut self, viewport: Viewport, player: &Player, elapsed: f32) {
+        let max_x = viewport.width - LIGHTING_CLOUD_DIM.0;
+        match self.pattern {
+            Pattern::Sweep { ref mut speed } => {
+                self.position.x += *speed * elapsed;
+                if self.position.x < 0.0 || self.position.x > max_x {
+                    *speed = -*speed;
+                    self.position.x = self.position.x.clamp(0.0, max_x);
+                }
+            }
+            Pattern::Predictive { ref mut target } => {
+                if target.is_none() && self.timer < TELEGRAPH_TIME {
+                    let center = player.position + vec2(PLAYER_DIM.0, PLAYER_DIM.1) / 2.0;
+                    let predicted = center + player.velocity * self.timer;
+                    *target = Some(vec2(predicted.x.clamp(0.0, viewport.width), predicted.y));
+                }
+                if let Some(target) = target {
+                    let goal = target.x - LIGHTING_CLOUD_DIM.0 / 2.0;
+                    let step = PREDICTIVE_SPEED * elapsed;
+                    self.position.x += (goal - self.position.x).clamp(-step, step);
+                }
+            }
+            Pattern::Single | Pattern::Double { .. } | Pattern::Bracket { .. } => {}
+        }
+    }
+
+    pub fn draw(&self, assets: &Assets, alpha: f32) {
         if matches!(self.state, State::Destroyed) {
             return;
         }
+        let position = self.last_position.lerp(self.position, alpha);
+        if let State::Waiting = self.state {
+            if self.timer < TELEGRAPH_TIME {
+                self.draw_telegraph(position);
+            }
+        }
         if let State::Striking { bolt_hitbox } = self.state {
             let texture = match (self.timer * 10.0) as usize % 3 {
                 0 => assets.lightning_1,
@@ -85,22 +176,67 @@ impl Lightning {
                 _ => assets.lightning_3,
             };
             draw_texture(texture, bolt_hitbox.x, bolt_hitbox.y, WHITE);
-            // draw_rectangle(
-            //     bolt_hitbox.x,
-            //     bolt_hitbox.y,
-            //     bolt_hitbox.w,
-            //     bolt_hitbox.h,
-            //     Color::from_rgba(255, 0, 0, 128),
-            // );
+            draw_hitbox(&bolt_hitbox);
+        }
+        draw_texture(assets.thunder_cloud, position.x, position.y, WHITE);
+        draw_hitbox(&self.cloud_hitbox);
+    }
+
+    fn draw_telegraph(&self, position: Vec2) {
+        let color = match self.pattern {
+            Pattern::Single | Pattern::Bracket { .. } => Color::from_rgba(255, 230, 90, 255),
+            Pattern::Sweep { .. } => Color::from_rgba(110, 220, 255, 255),
+            Pattern::Double { .. } => Color::from_rgba(255, 150, 60, 255),
+            Pattern::Predictive { .. } => Color::from_rgba(235, 70, 70, 255),
+        };
+        // Flashes faster as the strike gets closer
+        let flash = f32::sin((TELEGRAPH_TIME - self.timer) * (8.0 + (TELEGRAPH_TIME - self.timer) * 12.0));
+        let strip_alpha = 0.15 + 0.15 * flash.max(0.0);
+        let center_x = position.x + LIGHTING_CLOUD_DIM.0 / 2.0;
+        let top = position.y + LIGHTING_CLOUD_DIM.1 - 20.0;
+        draw_rectangle(
+            center_x - BOLT_WIDTH / 2.0,
+            top,
+            BOLT_WIDTH,
+            screen_height() * 2.0,
+            Color {
+                a: strip_alpha,
+                ..color
+            },
+        );
+
+        let mark_y = position.y + LIGHTING_CLOUD_DIM.1 - 8.0;
+        match self.pattern {
+            Pattern::Single => {}
+            Pattern::Sweep { speed } => {
+                let dir = speed.signum();
+                let tip = vec2(center_x + dir * 30.0, mark_y);
+                draw_triangle(tip, tip - vec2(dir * 14.0, 8.0), tip - vec2(dir * 14.0, -8.0), color);
+            }
+            Pattern::Double { strikes_left } => {
+                for i in 0..strikes_left {
+                    let offset = (i as f32 - (strikes_left - 1) as f32 / 2.0) * 16.0;
+                    draw_circle(center_x + offset, mark_y, 5.0, color);
+                }
+            }
+            Pattern::Bracket { side } => {
+                let dir = match side {
+                    HDirection::Left => -1.0,
+                    HDirection::Right => 1.0,
+                };
+                for offset in [0.0, 12.0] {
+                    let tip = vec2(center_x + dir * (BOLT_WIDTH / 2.0 + 14.0 + offset), mark_y);
+                    draw_triangle(tip, tip - vec2(dir * 10.0, 7.0), tip - vec2(dir * 10.0, -7.0), color);
+                }
+            }
+            Pattern::Predictive { target } => {
+                if let Some(target) = target {
+                    draw_circle_lines(target.x, target.y, 22.0, 3.0, color);
+                    draw_line(target.x - 30.0, target.y, target.x + 30.0, target.y, 2.0, color);
+                    draw_line(target.x, target.y - 30.0, target.x, target.y + 30.0, 2.0, color);
+                }
+            }
         }
-        // draw_rectangle(
-        //     self.cloud_hitbox.x,
-        //     self.cloud_hitbox.y,
-        //     self.cloud_hitbox.w,
-        //     self.cloud_hitbox.h,
-        //     Color::from_rgba(255, 0, 0, 128),
-        // );
-        draw_texture(assets.thunder_cloud, self.position.x, self.position.y, WHITE);
     }
 
     pub fn should_destroy(&self) -> bool {
@@ -1,38 +1,45 @@
 mod assets;
 mod bird;
+mod config;
 mod cursor;
+mod difficulty;
 mod fling;
+mod ghost;
+mod keys;
+mod landing;
 mod level_gen;
 mod lightning;
 mod player;
+mod replay;
+mod rng;
 mod scene;
 mod scenes;
-
-use std::{
-    collections::hash_map::DefaultHasher,
-    hash::{Hash, Hasher},
-};
+mod scores;
+mod squid;
+mod wave;
+mod wind;
+mod world;
 
 use assets::Assets;
+use config::config;
 use enum_map::EnumMap;
-use macroquad::{prelude::*, rand::srand};
-use scene::SceneManager;
-use scenes::menu_scene::MenuScene;
-use serde::Deserialize;
+use macroquad::prelude::*;
+use replay::Replay;
+use scene::{Scene, SceneManager};
+use scenes::{game_scene::GameScene, menu_scene::MenuScene};
+use serde::{Deserialize, Serialize};
 
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum HDirection {
     Left,
     Right,
 }
 
-#[derive(Deserialize)]
-struct Config {}
-
 fn window_conf() -> Conf {
     Conf {
         window_title: "Delirious Descent".to_owned(),
-        window_width: 400,
-        window_height: 640,
+        window_width: config().window_width,
+        window_height: config().window_height,
         window_resizable: false,
         high_dpi: false,
         icon: None,
@@ -42,12 +49,13 @@ fn window_conf() -> Conf {
 
 #[macroquad::main(window_conf)]
 async fn main() -> Result<(), String> {
-    let mut s = DefaultHasher::new();
-    instant::Instant::now().hash(&mut s);
-    let seed = s.finish();
-    srand(seed);
+    let args = parse_args()?;
+    let start_scene: Box<dyn Scene> = match args.replay {
+        Some(path) => GameScene::replay(Replay::load(&path)?),
+        None => MenuScene::with_seed(args.seed),
+    };
 
-    let mut scene_manager = SceneManager::new(MenuScene::new());
+    let mut scene_manager = SceneManager::new(start_scene);
     let mut assets = Assets::load().await;
     show_mouse(false);
 
@@ -58,6 +66,35 @@ async fn main() -> Result<(), String> {
     }
 }
 
+#[derive(Default)]
+struct Args {
+    seed: Option<u64>,
+    replay: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut parsed = Args::default();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                let value = args.next().ok_or("--seed requires a value")?;
+                let seed = value.parse().map_err(|_| format!("invalid seed: {}", value))?;
+                parsed.seed = Some(seed);
+            }
+            "--replay" => parsed.replay = Some(args.next().ok_or("--replay requires a path")?),
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+    Ok(parsed)
+}
+
+fn draw_hitbox(hitbox: &Rect) {
+    if config().debug.hitboxes {
+        draw_rectangle(hitbox.x, hitbox.y, hitbox.w, hitbox.h, Color::from_rgba(255, 0, 0, 128));
+    }
+}
+
 fn update_inputs<T>(inputs: &mut EnumMap<T, bool>, bindings: &EnumMap<T, Vec<KeyCode>>)
 where
     T: enum_map::EnumArray<bool> + enum_map::EnumArray<Vec<KeyCode>> + Copy,
@@ -3,8 +3,9 @@ use macroquad::prelude::*;
 
 use crate::{
     assets::Assets,
-    fling::{FlingKind, FlingThing},
-    scenes::game_scene::{Input, Sound},
+    draw_hitbox,
+    fling::{draw_rainbow, FlingKind, FlingThing},
+    world::{Input, Sound, Viewport},
     HDirection,
 };
 
@@ -12,90 +13,133 @@ pub const PLAYER_DIM: (f32, f32) = (64.0, 64.0);
 const HORIZONTAL_SPEED: f32 = 50.0;
 const BOOSTER_SPEED: f32 = 500.0;
 const BOOSTER_TIME: f32 = 0.25;
+const BOOST_COST: f32 = 1.0 / BOOSTER_TIME;
 const INVICIBILTY_TIME: f32 = 1.5;
+const RAINBOW_TIME: f32 = 6.0;
+const RAINBOW_BOUNCE: f32 = 150.0;
+const BOUNCE_GRAVITY: f32 = 60.0;
+pub const MAX_FUEL: f32 = 3.0;
+/// Where balloons beyond the three on the sprite are drawn, relative to the sprite, in the order they're added.
+const EXTRA_BALLOONS: [(f32, f32); 5] = [(32.0, -4.0), (2.0, 14.0), (62.0, 14.0), (-8.0, 30.0), (72.0, 30.0)];
+const BALLOON_COLORS: [Color; 3] = [
+    Color::new(0.35, 0.8, 0.4, 1.0),
+    Color::new(0.95, 0.6, 0.2, 1.0),
+    Color::new(0.75, 0.45, 0.9, 1.0),
+];
+pub const MAX_BALLOONS: usize = 3 + EXTRA_BALLOONS.len();
+const MAX_UPDRAFT: f32 = 0.75;
 
 enum State {
     Normal,
     Booster { dir: HDirection, timer: f32 },
     Landed,
     Bounced { timer: f32 },
+    Carried,
 }
 
 pub struct Player {
     state: State,
     pub position: Vec2,
+    last_position: Vec2,
     pub velocity: Vec2,
     balloons: usize,
+    max_balloons: usize,
     pub hitbox: Rect,
-    pub fuel: usize,
+    pub fuel: f32,
+    fuel_recharge: f32,
     boost_cooldown: f32,
     invincible: f32,
+    rainbow: f32,
 }
 
 impl Player {
-    pub fn new(starting_position: Vec2) -> Self {
+    pub fn new(starting_position: Vec2, balloons: usize, max_balloons: usize, fuel: f32, fuel_recharge: f32) -> Self {
+        let max_balloons = usize::min(usize::max(max_balloons, balloons), MAX_BALLOONS);
         let hitbox = Rect::new(starting_position.x + 8.0, starting_position.y + 8.0, 48.0, 48.0);
         Self {
             state: State::Normal,
             position: starting_position,
+            last_position: starting_position,
             velocity: Vec2::default(),
-            balloons: 3,
+            balloons: usize::min(balloons, max_balloons),
+            max_balloons,
             hitbox,
-            fuel: 3,
+            fuel: f32::min(fuel, MAX_FUEL),
+            fuel_recharge,
             boost_cooldown: 0.0,
             invincible: 0.0,
+            rainbow: 0.0,
         }
     }
 
-    pub fn update(&mut self, inputs: &EnumMap<Input, bool>, sounds: &mut EnumMap<Sound, bool>, elapsed: f32) {
-        if self.fuel > 0
+    pub fn update(
+        &mut self,
+        inputs: &EnumMap<Input, bool>,
+        wind: Vec2,
+        sounds: &mut EnumMap<Sound, bool>,
+        viewport: Viewport,
+        elapsed: f32,
+    ) {
+        self.last_position = self.position;
+        if self.fuel > 0.0
             && self.boost_cooldown <= 0.0
             && !matches!(self.state, State::Booster { .. })
             && !matches!(self.state, State::Landed)
         {
-            match (inputs[Input::BoostLeft], inputs[Input::BoostRight]) {
-                (true, false) => {
-                    sounds[Sound::Boost] = true;
-                    self.fuel -= 1;
-                    self.boost_cooldown = 0.25;
-                    self.state = State::Booster {
-                        dir: HDirection::Left,
-                        timer: 0.0,
-                    }
-                }
-                (false, true) => {
-                    sounds[Sound::Boost] = true;
-                    self.fuel -= 1;
-                    self.boost_cooldown = 0.25;
-                    self.state = State::Booster {
-                        dir: HDirection::Right,
-                        timer: 0.0,
-                    }
-                }
-                _ => {}
+            let dir = match (inputs[Input::BoostLeft], inputs[Input::BoostRight]) {
+                (true, false) => Some(HDirection::Left),
+                (false, true) => Some(HDirection::Right),
+                _ => None,
+            };
+            if let Some(dir) = dir {
+                sounds[Sound::Boost] = true;
+                self.boost_cooldown = 0.25;
+                self.state = State::Booster { dir, timer: 0.0 };
             }
         } else {
             self.boost_cooldown -= elapsed;
         }
+        if !matches!(self.state, State::Booster { .. } | State::Landed) {
+            self.fuel = f32::min(self.fuel + self.fuel_recharge * elapsed, MAX_FUEL);
+        }
 
         match self.state {
-            State::Normal => self.normal_update(inputs, elapsed),
-            State::Booster { .. } => self.booster_update(inputs, elapsed),
+            State::Normal => {
+                self.normal_update(inputs, elapsed);
+                self.drift(wind);
+            }
+            State::Booster { .. } => {
+                self.booster_update(inputs, elapsed);
+                self.drift(wind);
+            }
             State::Landed => self.velocity = Vec2::new(0.0, 0.0),
             State::Bounced { ref mut timer } => {
                 *timer -= elapsed;
-                self.velocity.y += 1.0;
+                self.velocity.y += BOUNCE_GRAVITY * elapsed;
+                self.velocity += wind * elapsed;
                 if *timer < 0.0 {
                     self.state = State::Normal;
                 }
             }
+            State::Carried => {
+                if inputs[Input::Down] {
+                    self.release();
+                }
+            }
         }
 
         self.position.x += self.velocity.x * elapsed;
-        self.position.x = f32::max(self.position.x, 0.0);
-        self.position.x = f32::min(self.position.x, screen_width() - PLAYER_DIM.0);
+        if viewport.wrap {
+            let shift = viewport.wrap_shift(self.position.x, PLAYER_DIM.0);
+            self.position.x += shift;
+            self.last_position.x += shift;
+        } else {
+            self.position.x = f32::max(self.position.x, 0.0);
+            self.position.x = f32::min(self.position.x, viewport.width - PLAYER_DIM.0);
+        }
         self.position.y += self.velocity.y * elapsed;
         self.invincible -= elapsed;
+        self.rainbow -= elapsed;
         self.update_hitbox();
     }
 
@@ -104,8 +148,8 @@ impl Player {
             0 => 500.0,
             1 => 100.0,
             2 => 50.0,
-            3 => 20.0,
-            _ => 0.0,
+            // More balloons keep slowing the fall, but never stop it
+            n => 60.0 / n as f32,
         };
         if inputs[Input::Down] {
             self.velocity.y += 200.0;
@@ -117,49 +161,59 @@ impl Player {
         }
     }
 
-    fn booster_update(&mut self, _inputs: &EnumMap<Input, bool>, elapsed: f32) {
+    /// Adds the wind on top of the velocity the player steers, which is set anew every step.
+    /// Updrafts slow the fall but never lift the player.
+    fn drift(&mut self, wind: Vec2) {
+        let mut drift = wind;
+        drift.y = f32::max(drift.y, -self.velocity.y.max(0.0) * MAX_UPDRAFT);
+        self.velocity += drift;
+    }
+
+    fn booster_update(&mut self, inputs: &EnumMap<Input, bool>, elapsed: f32) {
         if let State::Booster {
             ref mut dir,
             ref mut timer,
         } = self.state
         {
-            match dir {
-                HDirection::Left => self.velocity.x = -BOOSTER_SPEED,
-                HDirection::Right => self.velocity.x = BOOSTER_SPEED,
-            }
+            let held = match dir {
+                HDirection::Left => {
+                    self.velocity.x = -BOOSTER_SPEED;
+                    inputs[Input::BoostLeft]
+                }
+                HDirection::Right => {
+                    self.velocity.x = BOOSTER_SPEED;
+                    inputs[Input::BoostRight]
+                }
+            };
             *timer += elapsed;
+            self.fuel = f32::max(self.fuel - BOOST_COST * elapsed, 0.0);
             self.velocity.y = 0.0;
-            if *timer > BOOSTER_TIME {
+            if self.fuel <= 0.0 || *timer > BOOSTER_TIME && !held {
                 self.state = State::Normal;
             }
         }
     }
 
-    pub fn draw(&self, assets: &Assets) {
-        let texture = match self.balloons {
-            0 => assets.player_0,
-            1 => assets.player_1,
-            2 => assets.player_2,
-            _ => assets.player,
-        };
-        let color = if self.invincible > 0.0 && (self.invincible * 5.0) as usize % 2 == 0 {
+    pub fn draw(&self, assets: &Assets, alpha: f32) {
+        let position = self.last_position.lerp(self.position, alpha);
+        let color = if self.rainbow > 0.0 {
+            macroquad::color::hsl_to_rgb((self.rainbow * 0.5).fract(), 0.8, 0.8)
+        } else if self.invincible > 0.0 && ((self.invincible * 5.0) as usize).is_multiple_of(2) {
             RED
         } else {
             WHITE
         };
-        draw_texture(texture, self.position.x, self.position.y, color);
-        // draw_rectangle(
-        //     self.hitbox.x,
-        //     self.hitbox.y,
-        //     self.hitbox.w,
-        //     self.hitbox.h,
-        //     Color::from_rgba(255, 0, 0, 128),
-        // );
+        Self::draw_body(self.balloons, position, color, assets);
+        // Flickers out during the last second
+        if self.rainbow > 1.0 || self.rainbow > 0.0 && ((self.rainbow * 10.0) as usize).is_multiple_of(2) {
+            draw_rainbow(position + vec2(PLAYER_DIM.0 / 2.0, 8.0), 40.0, 0.6);
+        }
+        draw_hitbox(&self.hitbox);
 
         if let State::Booster { ref dir, .. } = self.state {
             let (flip, pos) = match dir {
-                HDirection::Left => (true, vec2(self.position.x + 52.0, self.position.y + 40.0)),
-                HDirection::Right => (false, vec2(self.position.x - 3.0, self.position.y + 40.0)),
+                HDirection::Left => (true, vec2(position.x + 52.0, position.y + 40.0)),
+                HDirection::Right => (false, vec2(position.x - 3.0, position.y + 40.0)),
             };
             draw_texture_ex(
                 assets.flame,
@@ -174,11 +228,57 @@ impl Player {
         }
     }
 
+    pub fn draw_body(balloons: usize, position: Vec2, color: Color, assets: &Assets) {
+        let texture = match balloons {
+            0 => assets.player_0,
+            1 => assets.player_1,
+            2 => assets.player_2,
+            _ => assets.player,
+        };
+        let extra = EXTRA_BALLOONS.iter().take(balloons.saturating_sub(3));
+        for (i, offset) in extra.enumerate() {
+            let balloon = BALLOON_COLORS[i % BALLOON_COLORS.len()];
+            let tinted = Color::new(balloon.r * color.r, balloon.g * color.g, balloon.b * color.b, color.a);
+            let knot = position + vec2(PLAYER_DIM.0 / 2.0, 40.0);
+            draw_balloon(position + vec2(offset.0, offset.1), knot, tinted);
+        }
+        draw_texture(texture, position.x, position.y, color);
+    }
+
+    pub fn balloons(&self) -> usize {
+        self.balloons
+    }
+
+    pub fn teleport(&mut self, position: Vec2) {
+        self.position = position;
+        self.last_position = position;
+        self.update_hitbox();
+    }
+
     fn update_hitbox(&mut self) {
         self.hitbox.x = self.position.x + (PLAYER_DIM.0 - self.hitbox.w) / 2.0;
         self.hitbox.y = self.position.y + (PLAYER_DIM.1 - self.hitbox.h) / 2.0;
     }
 
+    pub fn grab(&mut self, velocity: Vec2) {
+        self.state = State::Carried;
+        self.velocity = velocity;
+    }
+
+    pub fn carried(&self) -> bool {
+        matches!(self.state, State::Carried)
+    }
+
+    pub fn release(&mut self) {
+        if self.carried() {
+            self.state = State::Normal;
+        }
+    }
+
+    pub fn can_grab(&self) -> bool {
+        matches!(self.state, State::Normal)
+    }
+
     pub fn land(&mut self) {
         self.state = State::Landed;
     }
@@ -200,16 +300,49 @@ impl Player {
                 }
             }
             FlingKind::GoldCloud => {
-                self.fuel = std::cmp::min(self.fuel + 1, 3);
+                self.fuel = f32::min(self.fuel + 1.0, MAX_FUEL);
+            }
+            FlingKind::Rainbow => {
+                self.rainbow = RAINBOW_TIME;
+            }
+            FlingKind::Balloon => {
+                self.balloons = usize::min(self.balloons + 1, self.max_balloons);
             }
         }
     }
 
     pub fn bird_collision(&mut self, sounds: &mut EnumMap<Sound, bool>) {
-        self.take_damage(sounds);
+        self.take_hit(sounds);
     }
 
     pub fn lightning_collision(&mut self, sounds: &mut EnumMap<Sound, bool>) {
-        self.take_damage(sounds);
+        self.take_hit(sounds);
     }
+
+    fn take_hit(&mut self, sounds: &mut EnumMap<Sound, bool>) {
+        if self.rainbow > 0.0 {
+            self.velocity = vec2(self.velocity.x, -RAINBOW_BOUNCE);
+            self.state = State::Bounced { timer: 0.5 };
+        } else {
+            self.take_damage(sounds);
+        }
+    }
+}
+
+pub fn draw_balloon(center: Vec2, knot: Vec2, color: Color) {
+    draw_line(
+        center.x,
+        center.y + 10.0,
+        knot.x,
+        knot.y,
+        1.0,
+        Color::new(0.2, 0.2, 0.2, color.a),
+    );
+    draw_circle(center.x, center.y, 10.0, color);
+    draw_triangle(
+        center + vec2(0.0, 8.0),
+        center + vec2(-3.0, 13.0),
+        center + vec2(3.0, 13.0),
+        color,
+    );
 }
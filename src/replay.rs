@@ -0,0 +1,135 @@
+use std::fs;
+
+use enum_map::EnumMap;
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    difficulty::Difficulty,
+    world::{FrameInput, Input, Viewport},
+};
+
+pub const REPLAY_PATH: &str = "replay.ron";
+
+#[derive(Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    /// The play area the run was recorded in, since the level is laid out to fit it.
+    #[serde(default = "default_viewport")]
+    pub viewport: Viewport,
+    pub frames: Vec<Frame>,
+}
+
+// Replays saved before the viewport was recorded all ran in the original 400x640 window
+fn default_viewport() -> Viewport {
+    Viewport {
+        width: 400.0,
+        height: 640.0,
+        wrap: false,
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Frame {
+    pub elapsed: f32,
+    pub inputs: EnumMap<Input, bool>,
+    pub cursor: (f32, f32),
+    pub mouse_pressed: bool,
+    pub mouse_down: bool,
+}
+
+impl Replay {
+    pub fn new(seed: u64, difficulty: Difficulty, viewport: Viewport) -> Self {
+        Self {
+            seed,
+            difficulty,
+            viewport,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, input: &FrameInput, elapsed: f32) {
+        self.frames.push(Frame {
+            elapsed,
+            inputs: input.inputs,
+            cursor: (input.cursor.x, input.cursor.y),
+            mouse_pressed: input.mouse_pressed,
+            mouse_down: input.mouse_down,
+        });
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("could not read replay {}: {}", path, e))?;
+        let mut replay: Self = ron::from_str(&text).map_err(|e| format!("could not parse replay {}: {}", path, e))?;
+        // Replays from before scripts were saved with them only name theirs
+        replay.difficulty.load_script()?;
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let text = ron::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(path, text).map_err(|e| format!("could not write replay {}: {}", path, e))
+    }
+}
+
+impl Frame {
+    pub fn input(&self) -> FrameInput {
+        FrameInput {
+            inputs: self.inputs,
+            cursor: vec2(self.cursor.0, self.cursor.1),
+            mouse_pressed: self.mouse_pressed,
+            mouse_down: self.mouse_down,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        difficulty::load_difficulties,
+        world::{World, TIME_STEP},
+    };
+
+    #[test]
+    fn saved_replay_plays_back_the_same_run() {
+        // Gauntlet, so the wave script has to come back with the replay too
+        let difficulty = load_difficulties()
+            .into_iter()
+            .find(|difficulty| difficulty.name == "Gauntlet")
+            .unwrap();
+        let viewport = Viewport {
+            width: 360.0,
+            height: 600.0,
+            wrap: false,
+        };
+        let mut world = World::new(&difficulty, viewport, 7);
+        let mut recording = Replay::new(7, difficulty, world.viewport);
+        for step in 0..1200 {
+            let mut input = FrameInput::default();
+            input.inputs[Input::Left] = step % 300 < 100;
+            input.inputs[Input::Right] = step % 300 >= 200;
+            world.update(&input, TIME_STEP);
+            recording.record(&input, TIME_STEP);
+        }
+
+        let path = std::env::temp_dir().join(format!("ld50-replay-test-{}.ron", std::process::id()));
+        let path = path.to_str().unwrap();
+        recording.save(path).unwrap();
+        let replay = Replay::load(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(replay.frames.len(), 1200);
+        assert_eq!((replay.viewport.width, replay.viewport.height), (360.0, 600.0));
+        assert!(replay.difficulty.waves.is_some());
+        let mut played = World::new(&replay.difficulty, replay.viewport, replay.seed);
+        for frame in &replay.frames {
+            played.update(&frame.input(), frame.elapsed);
+        }
+        assert_eq!(
+            (played.time, played.player.position),
+            (world.time, world.player.position)
+        );
+    }
+}
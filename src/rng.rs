@@ -0,0 +1,73 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+const INCREMENT: u64 = 1442695040888963407;
+const MULTIPLIER: u64 = 6364136223846793005;
+
+#[derive(Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Self { state: 0 };
+        rng.rand();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.rand();
+        rng
+    }
+
+    pub fn rand(&mut self) -> u32 {
+        let old_state = self.state;
+        self.state = old_state.wrapping_mul(MULTIPLIER).wrapping_add(INCREMENT);
+        let xor_shifted = (((old_state >> 18) ^ old_state) >> 27) as u32;
+        let rot = (old_state >> 59) as u32;
+        xor_shifted.rotate_right(rot)
+    }
+
+    pub fn gen_range(&mut self, low: f32, high: f32) -> f32 {
+        // Only 24 bits fit in an f32 exactly, so dividing a full u32 could round up to 1.0
+        let r = (self.rand() >> 8) as f32 / (1u32 << 24) as f32;
+        let x = low + (high - low) * r;
+        // Adding `low` back can still round up to `high` when it is much bigger than the range
+        if x < high || high <= low {
+            x
+        } else {
+            high.next_down()
+        }
+    }
+}
+
+pub fn random_seed() -> u64 {
+    let mut s = DefaultHasher::new();
+    instant::Instant::now().hash(&mut s);
+    s.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_numbers() {
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.rand(), b.rand());
+        }
+    }
+
+    #[test]
+    fn gen_range_stays_below_high() {
+        let mut rng = Rng::new(7);
+        for _ in 0..100_000 {
+            let x = rng.gen_range(10.0, 390.0);
+            assert!((10.0..390.0).contains(&x));
+            let y = rng.gen_range(1999.9, 2000.0);
+            assert!((1999.9..2000.0).contains(&y));
+        }
+    }
+}
@@ -4,13 +4,23 @@ pub trait Scene {
     fn handle_input(&mut self);
     fn update(&mut self, elapsed: f32) -> SceneAction;
     fn render(&self, assets: &mut Assets);
+
+    fn resume(&mut self) {}
+
+    fn is_overlay(&self) -> bool {
+        false
+    }
 }
 
 pub enum SceneAction {
     Continue,
-    _Push(Box<dyn Scene>),
+    Push(Box<dyn Scene>),
     Replace(Box<dyn Scene>),
-    _Pop,
+    ReplaceAll(Box<dyn Scene>),
+    Pop,
+    /// Pops this scene and the one below it, like leaving a pause menu along with the game it paused.
+    PopBoth,
+    ReplaceBoth(Box<dyn Scene>),
 }
 
 pub struct SceneManager {
@@ -32,16 +42,39 @@ impl SceneManager {
 
         current_scene.handle_input();
         let action = current_scene.update(elapsed);
-        current_scene.render(assets);
+
+        let first_visible = self.stack.iter().rposition(|scene| !scene.is_overlay()).unwrap_or(0);
+        for scene in self.stack[first_visible..].iter() {
+            scene.render(assets);
+        }
 
         match action {
             SceneAction::Continue => {}
-            SceneAction::_Push(x) => self.stack.push(x),
+            SceneAction::Push(x) => self.stack.push(x),
             SceneAction::Replace(x) => {
                 drop(self.stack.pop());
                 self.stack.push(x);
             }
-            SceneAction::_Pop => drop(self.stack.pop()),
+            SceneAction::ReplaceAll(x) => {
+                self.stack.clear();
+                self.stack.push(x);
+            }
+            SceneAction::Pop => {
+                drop(self.stack.pop());
+                if let Some(scene) = self.stack.last_mut() {
+                    scene.resume();
+                }
+            }
+            SceneAction::PopBoth => {
+                self.stack.truncate(self.stack.len().saturating_sub(2));
+                if let Some(scene) = self.stack.last_mut() {
+                    scene.resume();
+                }
+            }
+            SceneAction::ReplaceBoth(x) => {
+                self.stack.truncate(self.stack.len().saturating_sub(2));
+                self.stack.push(x);
+            }
         }
     }
 }
@@ -0,0 +1,196 @@
+use enum_map::EnumMap;
+use macroquad::prelude::*;
+
+use super::{
+    draw_label,
+    game_scene::RESERVED_KEYS,
+    menu_scene::{Button, MenuAction, MenuScene},
+};
+use crate::{
+    assets::Assets,
+    config::{config, load_bindings, save_bindings},
+    cursor::Cursor,
+    keys::key_name,
+    scene::{Scene, SceneAction},
+    world::Input,
+};
+
+const ROW_TOP: f32 = 100.0;
+const ROW_HEIGHT: f32 = 62.0;
+
+#[derive(Clone, Copy)]
+enum Capture {
+    Replace(Input),
+    Add(Input),
+}
+
+pub struct BindingsScene {
+    cursor: Cursor,
+    bindings: EnumMap<Input, Vec<KeyCode>>,
+    capture: Option<Capture>,
+    message: String,
+    reset_button: Button,
+    back_button: Button,
+    pushed: bool,
+}
+
+impl BindingsScene {
+    pub fn new() -> Box<Self> {
+        Self::with_return(false)
+    }
+
+    pub fn pushed() -> Box<Self> {
+        Self::with_return(true)
+    }
+
+    fn with_return(pushed: bool) -> Box<Self> {
+        set_cursor_grab(false);
+        set_default_camera();
+
+        Box::new(Self {
+            cursor: Cursor::new(),
+            bindings: load_bindings(),
+            capture: None,
+            message: String::new(),
+            reset_button: Button {
+                rect: Rect::new(25.0, 540.0, 170.0, 70.0),
+                action: MenuAction::ResetBindings,
+            },
+            back_button: Button {
+                rect: Rect::new(205.0, 540.0, 170.0, 70.0),
+                action: MenuAction::Return,
+            },
+            pushed,
+        })
+    }
+
+    fn set_rect(row: usize) -> Rect {
+        Rect::new(250.0, ROW_TOP + row as f32 * ROW_HEIGHT + 8.0, 60.0, 46.0)
+    }
+
+    fn add_rect(row: usize) -> Rect {
+        Rect::new(318.0, ROW_TOP + row as f32 * ROW_HEIGHT + 8.0, 60.0, 46.0)
+    }
+
+    fn bind(&mut self, capture: Capture, key: KeyCode) {
+        let input = match capture {
+            Capture::Replace(input) | Capture::Add(input) => input,
+        };
+        if let Some((_, action)) = RESERVED_KEYS.iter().find(|(reserved, _)| *reserved == key) {
+            self.message = format!("{} is used for {}", key_name(key), action);
+            return;
+        }
+        let conflict = self
+            .bindings
+            .iter()
+            .find(|(other, keys)| *other != input && keys.contains(&key))
+            .map(|(other, _)| other);
+        if let Some(other) = conflict {
+            self.message = format!("{} is already bound to {:?}", key_name(key), other);
+            return;
+        }
+
+        match capture {
+            Capture::Replace(_) => self.bindings[input] = vec![key],
+            Capture::Add(_) => {
+                if !self.bindings[input].contains(&key) {
+                    self.bindings[input].push(key);
+                }
+            }
+        }
+        self.save();
+    }
+
+    fn save(&mut self) {
+        self.message = match save_bindings(&self.bindings) {
+            Ok(()) => String::new(),
+            Err(e) => e,
+        };
+    }
+}
+
+impl Scene for BindingsScene {
+    fn handle_input(&mut self) {
+        if let Some(capture) = self.capture {
+            if let Some(key) = get_last_key_pressed() {
+                self.capture = None;
+                self.message = String::new();
+                if key != KeyCode::Escape {
+                    self.bind(capture, key);
+                }
+            }
+        }
+    }
+
+    fn update(&mut self, _elapsed: f32) -> SceneAction {
+        self.cursor.basic_update();
+        if self.capture.is_some() || !is_mouse_button_pressed(MouseButton::Left) {
+            return SceneAction::Continue;
+        }
+
+        let mut mouse_pos = Vec2::default();
+        (mouse_pos.x, mouse_pos.y) = mouse_position();
+        let inputs: Vec<Input> = self.bindings.iter().map(|(input, _)| input).collect();
+        for (row, input) in inputs.into_iter().enumerate() {
+            if Self::set_rect(row).contains(mouse_pos) {
+                self.capture = Some(Capture::Replace(input));
+            } else if Self::add_rect(row).contains(mouse_pos) {
+                self.capture = Some(Capture::Add(input));
+            }
+        }
+        if self.capture.is_some() {
+            self.message = "Press a key (Escape to cancel)".to_string();
+        }
+
+        let mut action = MenuAction::None;
+        if self.reset_button.rect.contains(mouse_pos) {
+            action = self.reset_button.action;
+        }
+        if self.back_button.rect.contains(mouse_pos) {
+            action = self.back_button.action;
+        }
+        match action {
+            MenuAction::ResetBindings => {
+                self.bindings = config().bindings.clone();
+                self.save();
+                SceneAction::Continue
+            }
+            MenuAction::Return if self.pushed => SceneAction::Pop,
+            MenuAction::Return => SceneAction::Replace(MenuScene::new()),
+            _ => SceneAction::Continue,
+        }
+    }
+
+    fn render(&self, assets: &mut Assets) {
+        set_default_camera();
+        clear_background(Color::from_rgba(52, 62, 65, 255));
+        draw_rectangle(0.0, 20.0, screen_width(), 60.0, Color::from_rgba(196, 84, 91, 255));
+        draw_label("Controls", 25.0, 65.0, 48, assets);
+
+        let capturing = self.capture.map(|capture| match capture {
+            Capture::Replace(input) | Capture::Add(input) => input,
+        });
+        for (row, (input, keys)) in self.bindings.iter().enumerate() {
+            let y = ROW_TOP + row as f32 * ROW_HEIGHT;
+            if capturing == Some(input) {
+                draw_rectangle(0.0, y, screen_width(), ROW_HEIGHT, Color::from_rgba(196, 84, 91, 255));
+            }
+            draw_label(&format!("{:?}", input), 15.0, y + 28.0, 28, assets);
+            let names: Vec<String> = keys.iter().map(|key| key_name(*key)).collect();
+            draw_label(&names.join(", "), 15.0, y + 52.0, 20, assets);
+
+            for (rect, label) in [(Self::set_rect(row), "Set"), (Self::add_rect(row), "Add")] {
+                draw_rectangle(rect.x, rect.y, rect.w, rect.h, GRAY);
+                draw_label(label, rect.x + 12.0, rect.y + 31.0, 24, assets);
+            }
+        }
+
+        draw_label(&self.message, 15.0, 520.0, 20, assets);
+        for (button, label) in [(&self.reset_button, "Reset"), (&self.back_button, "Back")] {
+            let rect = button.rect;
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, GRAY);
+            draw_label(label, rect.x + 35.0, rect.y + 46.0, 40, assets);
+        }
+        self.cursor.draw();
+    }
+}
@@ -0,0 +1,419 @@
+use macroquad::prelude::*;
+
+use super::{game_scene::GameScene, menu_scene::MenuScene};
+use crate::{
+    assets::Assets,
+    bird::{Bird, BirdKind, BIRD_SIZE},
+    config::config,
+    cursor::Cursor,
+    difficulty::{load_difficulties, Difficulty},
+    fling::{FlingKind, FlingThing, THING_DIM},
+    lightning::{Lightning, LightningPattern, Pattern, LIGHTING_CLOUD_DIM},
+    scene::{Scene, SceneAction},
+    wave::{Trigger, WaveEvent, WaveScript, WaveSpawn, SCRIPT_DIR},
+    world::Viewport,
+    HDirection,
+};
+
+const EDITOR_SCRIPT: &str = "custom";
+const SCROLL_SPEED: f32 = 600.0;
+const WHEEL_STEP: f32 = 60.0;
+const MIN_DEPTH: f32 = 800.0;
+const BIRD_KINDS: [BirdKind; 4] = [BirdKind::Basic, BirdKind::Diver, BirdKind::Flock, BirdKind::Circler];
+const PATTERNS: [LightningPattern; 5] = [
+    LightningPattern::Single,
+    LightningPattern::Sweep,
+    LightningPattern::Double,
+    LightningPattern::Bracket,
+    LightningPattern::Predictive,
+];
+
+#[derive(Clone, Copy)]
+enum Tool {
+    Cloud,
+    GoldCloud,
+    Bird(usize),
+    Lightning(usize),
+}
+
+pub struct EditorScene {
+    cursor: Cursor,
+    viewport: Viewport,
+    camera: Camera2D,
+    script: WaveScript,
+    level_depth: f32,
+    difficulty: Difficulty,
+    tool: Tool,
+    dragging: Option<(usize, Vec2)>,
+    status: String,
+}
+
+impl EditorScene {
+    pub fn new() -> Box<Self> {
+        set_cursor_grab(false);
+        let difficulties = load_difficulties();
+        let difficulty = difficulties
+            .iter()
+            .find(|difficulty| difficulty.name == config().difficulty)
+            .unwrap_or(&difficulties[0])
+            .clone();
+        let viewport = Viewport::screen();
+        let camera = Camera2D::from_display_rect(Rect::new(0.0, 0.0, viewport.width, viewport.height));
+
+        let mut scene = Self {
+            cursor: Cursor::new(),
+            viewport,
+            camera,
+            script: WaveScript {
+                random_hazards: false,
+                random_clouds: false,
+                level_depth: None,
+                events: Vec::new(),
+            },
+            level_depth: difficulty.level_depth,
+            difficulty,
+            tool: Tool::Cloud,
+            dragging: None,
+            status: String::new(),
+        };
+        // Carry on with the last saved level, if there is one
+        scene.load().ok();
+        Box::new(scene)
+    }
+
+    fn load(&mut self) -> Result<(), String> {
+        let script = WaveScript::load(EDITOR_SCRIPT)?;
+        self.level_depth = script.level_depth.unwrap_or(self.difficulty.level_depth);
+        self.script = script;
+        self.dragging = None;
+        self.status = format!("Loaded {}/{}.ron", SCRIPT_DIR, EDITOR_SCRIPT);
+        Ok(())
+    }
+
+    fn save(&mut self) -> bool {
+        self.script.level_depth = Some(self.level_depth);
+        match self.script.save(EDITOR_SCRIPT) {
+            Ok(()) => {
+                self.status = format!("Saved {}/{}.ron", SCRIPT_DIR, EDITOR_SCRIPT);
+                true
+            }
+            Err(e) => {
+                self.status = format!("Could not save: {}", e);
+                false
+            }
+        }
+    }
+
+    fn test(&mut self, start_y: f32) -> SceneAction {
+        if !self.save() {
+            return SceneAction::Continue;
+        }
+        let difficulty = Difficulty {
+            script: Some(EDITOR_SCRIPT.to_string()),
+            waves: Some(self.script.clone()),
+            ..self.difficulty.clone()
+        };
+        SceneAction::Push(GameScene::test(difficulty, start_y))
+    }
+
+    fn camera_y(&self) -> f32 {
+        self.camera.target.y
+    }
+
+    fn scroll(&mut self, amount: f32) {
+        let half = self.viewport.height / 2.0;
+        self.camera.target.y =
+            (self.camera.target.y + amount).clamp(half, f32::max(half, self.level_depth + 60.0 - half));
+    }
+
+    fn mouse_in_world(&self) -> Vec2 {
+        let (x, y) = mouse_position();
+        vec2(x, y + self.camera_y() - self.viewport.height / 2.0)
+    }
+
+    fn bird_height(&self) -> f32 {
+        self.viewport.height * 2.0 / 3.0
+    }
+
+    /// Where an event shows up in the level. Birds and thunder clouds only have a place if triggered by depth.
+    fn marker(&self, event: &WaveEvent) -> Option<Rect> {
+        // When a depth is reached, the player is a sixth of the screen below its top
+        let top_at = |depth: f32| depth - self.viewport.height / 6.0;
+        match (event.at, event.spawn) {
+            (_, WaveSpawn::Cloud { x, y, .. }) => Some(Rect::new(x, y, THING_DIM.0, THING_DIM.1)),
+            (Trigger::Depth(depth), WaveSpawn::Bird { side, height, .. }) => {
+                let x = match side {
+                    HDirection::Left => 4.0,
+                    HDirection::Right => self.viewport.width - BIRD_SIZE - 4.0,
+                };
+                Some(Rect::new(x, top_at(depth) + height, BIRD_SIZE, BIRD_SIZE))
+            }
+            (Trigger::Depth(depth), WaveSpawn::Lightning { offset, .. }) => Some(Rect::new(
+                (self.viewport.width - LIGHTING_CLOUD_DIM.0) / 2.0 + offset,
+                depth,
+                LIGHTING_CLOUD_DIM.0,
+                LIGHTING_CLOUD_DIM.1,
+            )),
+            (Trigger::Time(_), _) => None,
+        }
+    }
+
+    fn moved(&self, event: WaveEvent, corner: Vec2) -> WaveEvent {
+        match event.spawn {
+            WaveSpawn::Cloud { kind, .. } => WaveEvent {
+                at: event.at,
+                spawn: WaveSpawn::Cloud {
+                    kind,
+                    x: corner.x,
+                    y: corner.y,
+                },
+            },
+            WaveSpawn::Bird { height, kind, .. } => {
+                let side = if corner.x + BIRD_SIZE / 2.0 < self.viewport.width / 2.0 {
+                    HDirection::Left
+                } else {
+                    HDirection::Right
+                };
+                WaveEvent {
+                    at: Trigger::Depth(corner.y + self.viewport.height / 6.0 - height),
+                    spawn: WaveSpawn::Bird { side, height, kind },
+                }
+            }
+            WaveSpawn::Lightning { pattern, .. } => WaveEvent {
+                at: Trigger::Depth(corner.y),
+                spawn: WaveSpawn::Lightning {
+                    offset: corner.x + LIGHTING_CLOUD_DIM.0 / 2.0 - self.viewport.width / 2.0,
+                    pattern,
+                },
+            },
+        }
+    }
+
+    fn place(&self, position: Vec2) -> WaveEvent {
+        let (spawn, size) = match self.tool {
+            Tool::Cloud | Tool::GoldCloud => {
+                let kind = match self.tool {
+                    Tool::GoldCloud => FlingKind::GoldCloud,
+                    _ => FlingKind::Cloud,
+                };
+                (
+                    WaveSpawn::Cloud { kind, x: 0.0, y: 0.0 },
+                    vec2(THING_DIM.0, THING_DIM.1),
+                )
+            }
+            Tool::Bird(kind) => (
+                WaveSpawn::Bird {
+                    side: HDirection::Left,
+                    height: self.bird_height(),
+                    kind: BIRD_KINDS[kind],
+                },
+                vec2(BIRD_SIZE, BIRD_SIZE),
+            ),
+            Tool::Lightning(pattern) => (
+                WaveSpawn::Lightning {
+                    offset: 0.0,
+                    pattern: PATTERNS[pattern],
+                },
+                vec2(LIGHTING_CLOUD_DIM.0, LIGHTING_CLOUD_DIM.1),
+            ),
+        };
+        let event = WaveEvent {
+            at: Trigger::Time(0.0),
+            spawn,
+        };
+        self.moved(event, position - size / 2.0)
+    }
+
+    fn event_at(&self, position: Vec2) -> Option<usize> {
+        self.script
+            .events
+            .iter()
+            .rposition(|event| self.marker(event).is_some_and(|marker| marker.contains(position)))
+    }
+
+    fn tool_name(&self) -> String {
+        match self.tool {
+            Tool::Cloud => "Cloud".to_string(),
+            Tool::GoldCloud => "Gold cloud".to_string(),
+            Tool::Bird(kind) => format!("Bird: {:?}", BIRD_KINDS[kind]),
+            Tool::Lightning(pattern) => format!("Thunder: {:?}", PATTERNS[pattern]),
+        }
+    }
+}
+
+impl Scene for EditorScene {
+    fn handle_input(&mut self) {
+        let (_, wheel) = mouse_wheel();
+        if wheel != 0.0 {
+            self.scroll(-wheel.signum() * WHEEL_STEP);
+        }
+        for (key, tool) in [
+            (KeyCode::Key1, Tool::Cloud),
+            (KeyCode::Key2, Tool::GoldCloud),
+            (KeyCode::Key3, Tool::Bird(0)),
+            (KeyCode::Key4, Tool::Lightning(0)),
+        ] {
+            if is_key_pressed(key) {
+                self.tool = tool;
+            }
+        }
+        if is_key_pressed(KeyCode::Tab) {
+            self.tool = match self.tool {
+                Tool::Bird(kind) => Tool::Bird((kind + 1) % BIRD_KINDS.len()),
+                Tool::Lightning(pattern) => Tool::Lightning((pattern + 1) % PATTERNS.len()),
+                tool => tool,
+            };
+        }
+    }
+
+    fn update(&mut self, elapsed: f32) -> SceneAction {
+        self.cursor.basic_update();
+        if is_key_pressed(KeyCode::Escape) {
+            return SceneAction::Replace(MenuScene::new());
+        }
+        if is_key_down(KeyCode::Up) {
+            self.scroll(-SCROLL_SPEED * elapsed);
+        }
+        if is_key_down(KeyCode::Down) {
+            self.scroll(SCROLL_SPEED * elapsed);
+        }
+
+        let mouse = self.mouse_in_world();
+        if is_mouse_button_pressed(MouseButton::Left) {
+            let index = match self.event_at(mouse) {
+                Some(index) => index,
+                None => {
+                    let event = self.place(mouse);
+                    self.script.events.push(event);
+                    self.script.events.len() - 1
+                }
+            };
+            if let Some(marker) = self.marker(&self.script.events[index]) {
+                self.dragging = Some((index, mouse - vec2(marker.x, marker.y)));
+            }
+        }
+        if let Some((index, grab)) = self.dragging {
+            if is_mouse_button_down(MouseButton::Left) {
+                self.script.events[index] = self.moved(self.script.events[index], mouse - grab);
+            } else {
+                self.dragging = None;
+            }
+        }
+        if is_mouse_button_pressed(MouseButton::Right) && self.dragging.is_none() {
+            if let Some(index) = self.event_at(mouse) {
+                self.script.events.remove(index);
+            }
+        }
+
+        if is_key_pressed(KeyCode::G) {
+            self.level_depth = f32::max(mouse.y, MIN_DEPTH);
+            self.scroll(0.0);
+        }
+        if is_key_pressed(KeyCode::S) {
+            self.save();
+        }
+        if is_key_pressed(KeyCode::L) {
+            if let Err(e) = self.load() {
+                self.status = format!("Could not load: {}", e);
+            }
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            return self.test(0.0);
+        }
+        if is_key_pressed(KeyCode::P) {
+            // Start where the camera shows the same view as the editor
+            return self.test(self.camera_y() - self.viewport.height / 3.0);
+        }
+        SceneAction::Continue
+    }
+
+    fn render(&self, assets: &mut Assets) {
+        set_camera(&self.camera);
+        draw_texture(assets.background, 0.0, -640.0, WHITE);
+        draw_texture(assets.ground, 0.0, self.level_depth, WHITE);
+
+        // Depth markings down the side
+        let top = self.camera_y() - self.viewport.height / 2.0;
+        let mut depth = (top / 100.0).floor() * 100.0;
+        while depth < top + self.viewport.height {
+            let length = if depth % 500.0 == 0.0 { 24.0 } else { 10.0 };
+            draw_line(0.0, depth, length, depth, 2.0, Color::from_rgba(255, 255, 255, 160));
+            if depth % 500.0 == 0.0 {
+                draw_text(&format!("{}", depth), 28.0, depth + 5.0, 18.0, WHITE);
+            }
+            depth += 100.0;
+        }
+
+        let trigger_color = Color::from_rgba(196, 84, 91, 160);
+        for event in self.script.events.iter() {
+            let marker = match self.marker(event) {
+                Some(marker) => marker,
+                None => continue,
+            };
+            if let Trigger::Depth(depth) = event.at {
+                draw_line(0.0, depth, self.viewport.width, depth, 1.0, trigger_color);
+            }
+            match event.spawn {
+                WaveSpawn::Cloud { kind, .. } => FlingThing::new(kind, vec2(marker.x, marker.y)).draw(assets, 1.0),
+                WaveSpawn::Bird { side, kind, .. } => {
+                    let dir = match side {
+                        HDirection::Left => HDirection::Right,
+                        HDirection::Right => HDirection::Left,
+                    };
+                    Bird::preview(kind, vec2(marker.x, marker.y), dir, self.viewport).draw(assets, 1.0);
+                }
+                WaveSpawn::Lightning { .. } => {
+                    Lightning::new(vec2(marker.x, marker.y), Pattern::Single).draw(assets, 1.0);
+                }
+            }
+        }
+
+        set_default_camera();
+        draw_rectangle(0.0, 0.0, self.viewport.width, 40.0, BLACK);
+        let timed = self
+            .script
+            .events
+            .iter()
+            .filter(|event| self.marker(event).is_none())
+            .count();
+        let header = match timed {
+            0 => self.tool_name(),
+            n => format!("{}  ({} timed)", self.tool_name(), n),
+        };
+        draw_text_ex(
+            &header,
+            10.0,
+            28.0,
+            TextParams {
+                font: assets.font,
+                font_size: 24,
+                ..Default::default()
+            },
+        );
+
+        let help = [
+            "1-4 tool, Tab kind, drag to move, right click delete",
+            "G ground at mouse, S save, L load",
+            "Enter play, P play from here, Esc menu",
+        ];
+        let bottom = self.viewport.height - 10.0;
+        draw_rectangle(
+            0.0,
+            bottom - 60.0,
+            self.viewport.width,
+            70.0,
+            Color::from_rgba(0, 0, 0, 160),
+        );
+        for (i, line) in help.iter().enumerate() {
+            draw_text(line, 10.0, bottom - 44.0 + i as f32 * 18.0, 18.0, WHITE);
+        }
+        if !self.status.is_empty() {
+            draw_text(&self.status, 10.0, 58.0, 18.0, WHITE);
+        }
+        self.cursor.draw();
+    }
+
+    fn resume(&mut self) {
+        set_cursor_grab(false);
+    }
+}
@@ -7,49 +7,102 @@ use super::{
 use crate::{
     assets::Assets,
     cursor::Cursor,
+    landing::Landing,
     scene::{Scene, SceneAction},
+    scores::{draw_scores, HighScores, Score},
 };
 
+const MAX_NAME_LENGTH: usize = 12;
+
+pub struct RunResult {
+    pub time: f32,
+    pub seed: u64,
+    pub difficulty: String,
+    pub landing: Option<&'static Landing>,
+    /// Replayed runs are shown but never enter the high scores.
+    pub replayed: bool,
+}
+
 pub struct EndScene {
     cursor: Cursor,
     button: Button,
-    time: f32,
+    result: RunResult,
     words: String,
+    scores: HighScores,
+    rank: Option<usize>,
+    name: Option<String>,
 }
 
 impl EndScene {
-    pub fn new(time: f32) -> Box<Self> {
+    pub fn new(result: RunResult) -> Box<Self> {
         set_cursor_grab(false);
         set_default_camera();
 
-        let words = if time < 30.0 {
-            "Ouch...".to_string()
-        } else if time < 60.0 {
-            "Float like a rock".to_string()
-        } else if time < 75.0 {
-            "Eh... Good enough".to_string()
-        } else if time < 90.0 {
-            "Great work".to_string()
-        } else if time < 105.0 {
-            "Top tier".to_string()
+        let time = result.time;
+
+        let words = match result.landing {
+            Some(landing) => landing.message.clone(),
+            None => "The replay ended before the landing.".to_string(),
+        };
+
+        let scores = HighScores::load();
+        let rank = if result.replayed {
+            None
         } else {
-            "Thanks for bothering".to_string()
+            scores.rank(&result.difficulty, time)
         };
 
         Box::new(Self {
             cursor: Cursor::new(),
             button: Button {
-                rect: Rect::new(25.0, 500.0, 350.0, 100.0),
+                rect: Rect::new(25.0, 515.0, 350.0, 100.0),
                 action: MenuAction::Return,
             },
-            time,
+            result,
             words,
+            scores,
+            rank,
+            name: rank.map(|_| String::new()),
         })
     }
+
+    fn save_score(&mut self) {
+        let name = match self.name.take() {
+            Some(name) => name,
+            None => return,
+        };
+        let name = match name.trim() {
+            "" => "Anonymous".to_string(),
+            name => name.to_string(),
+        };
+        let score = Score {
+            name,
+            time: self.result.time,
+            seed: self.result.seed,
+        };
+        self.scores.insert(&self.result.difficulty, score);
+        if let Err(e) = self.scores.save() {
+            error!("{}", e);
+        }
+    }
 }
 
 impl Scene for EndScene {
-    fn handle_input(&mut self) {}
+    fn handle_input(&mut self) {
+        if let Some(ref mut name) = self.name {
+            while let Some(c) = get_char_pressed() {
+                if (c.is_alphanumeric() || c == ' ') && name.chars().count() < MAX_NAME_LENGTH {
+                    name.push(c);
+                }
+            }
+            if is_key_pressed(KeyCode::Backspace) {
+                name.pop();
+            }
+            if is_key_pressed(KeyCode::Enter) {
+                self.save_score();
+            }
+        }
+    }
 
     fn update(&mut self, _elapsed: f32) -> SceneAction {
         self.cursor.basic_update();
@@ -62,16 +115,20 @@ impl Scene for EndScene {
             }
         }
         match action {
-            MenuAction::Return => SceneAction::Replace(MenuScene::new()),
+            MenuAction::Return => {
+                self.save_score();
+                SceneAction::Replace(MenuScene::new())
+            }
             _ => SceneAction::Continue,
         }
     }
 
     fn render(&self, assets: &mut Assets) {
+        set_default_camera();
         clear_background(Color::from_rgba(52, 62, 65, 255));
         draw_rectangle(0.0, 75.0, screen_width(), 100.0, Color::from_rgba(196, 84, 91, 255));
         draw_text_ex(
-            &format_time(self.time),
+            &format_time(self.result.time),
             50.0,
             150.0,
             TextParams {
@@ -83,13 +140,42 @@ impl Scene for EndScene {
         draw_text_ex(
             &self.words,
             25.0,
-            300.0,
+            230.0,
             TextParams {
                 font: assets.font,
                 font_size: 32,
                 ..Default::default()
             },
         );
+        draw_text_ex(
+            &format!("{} - Seed: {}", self.result.difficulty, self.result.seed),
+            25.0,
+            262.0,
+            TextParams {
+                font: assets.font,
+                font_size: 20,
+                ..Default::default()
+            },
+        );
+        draw_scores(
+            self.scores.table(&self.result.difficulty),
+            300.0,
+            self.rank,
+            self.name.as_deref(),
+            assets,
+        );
+        if self.name.is_some() {
+            draw_text_ex(
+                "New best time! Type your name and press Enter",
+                25.0,
+                505.0,
+                TextParams {
+                    font: assets.font,
+                    font_size: 16,
+                    ..Default::default()
+                },
+            );
+        }
         draw_rectangle(
             self.button.rect.x,
             self.button.rect.y,
@@ -1,249 +1,263 @@
-use enum_map::{enum_map, Enum, EnumMap};
-use macroquad::{audio::play_sound_once, prelude::*};
+use enum_map::EnumMap;
+use macroquad::{
+    audio::{play_sound, PlaySoundParams},
+    prelude::*,
+};
 
-use super::{end_scece::EndScene, menu_scene::MenuScene};
+use super::{
+    end_scece::{EndScene, RunResult},
+    menu_scene::MenuScene,
+    pause_scene::{PauseScene, PausedRun},
+};
 use crate::{
     assets::Assets,
-    bird::Bird,
-    cursor::Cursor,
-    fling::FlingThing,
-    level_gen::{self, Difficulty, Generator},
-    lightning::Lightning,
-    player::Player,
+    config::{config, load_bindings},
+    difficulty::Difficulty,
+    ghost::Ghost,
+    player::MAX_FUEL,
+    replay::{Replay, REPLAY_PATH},
     scene::{Scene, SceneAction},
-    update_inputs,
+    update_inputs, wind,
+    world::{FrameInput, Input, Sound, Viewport, World, TIME_STEP},
 };
 
-#[derive(Enum, Clone, Copy, Debug)]
-pub enum Input {
-    Up,
-    Down,
-    Left,
-    Right,
-    BoostLeft,
-    BoostRight,
-}
+const QUIT_KEY: KeyCode = KeyCode::R;
+const PAUSE_KEY: KeyCode = KeyCode::Escape;
+pub const RESERVED_KEYS: [(KeyCode, &str); 2] = [(QUIT_KEY, "quitting"), (PAUSE_KEY, "pausing")];
 
-#[derive(Enum, Debug)]
-pub enum Sound {
-    Hit,
-    Lightning,
-    Fling,
-    Boost,
-    End,
+const MAX_FRAME_TIME: f32 = 0.25;
+
+enum Mode {
+    Live { recording: Replay },
+    Replay { replay: Replay, frame: usize },
+    Test { difficulty: Difficulty, start_y: f32 },
 }
 
 pub struct GameScene {
-    player: Player,
-    cursor: Cursor,
-    generator: Generator,
-    fling_things: Vec<FlingThing>,
-    birds: Vec<Bird>,
-    lightning: Option<Lightning>,
-    time: f32,
-    ground_position: f32,
-    mouse_captured: bool,
-    end_timer: f32,
-    sounds: EnumMap<Sound, bool>,
+    world: World,
+    mode: Mode,
+    accumulator: f32,
+    best_ghost: Option<Ghost>,
+    ghost: Ghost,
 
     camera: Camera2D,
-    inputs: EnumMap<Input, bool>,
+    input: FrameInput,
     bindings: EnumMap<Input, Vec<KeyCode>>,
 }
 
 impl GameScene {
-    pub fn new(difficulty: Difficulty) -> Box<Self> {
-        let bindings = enum_map! {
-            Input::Up => vec![KeyCode::W, KeyCode::Up],
-            Input::Down => vec![KeyCode::S, KeyCode::Down],
-            Input::Left => vec![KeyCode::A, KeyCode::Left],
-            Input::Right => vec![KeyCode::D, KeyCode::Right],
-            Input::BoostLeft => vec![KeyCode::Q, KeyCode::RightControl],
-            Input::BoostRight => vec![KeyCode::E, KeyCode::Kp0],
-        };
+    pub fn new(difficulty: Difficulty, seed: u64) -> Box<Self> {
+        let world = World::new(&difficulty, Viewport::screen(), seed);
+        let recording = Replay::new(seed, difficulty, world.viewport);
+        Self::with_mode(world, Mode::Live { recording })
+    }
+
+    pub fn replay(replay: Replay) -> Box<Self> {
+        let world = World::new(&replay.difficulty, replay.viewport, replay.seed);
+        Self::with_mode(world, Mode::Replay { replay, frame: 0 })
+    }
+
+    pub fn test(difficulty: Difficulty, start_y: f32) -> Box<Self> {
+        let mut world = World::new(&difficulty, Viewport::screen(), 0);
+        world.start_at(start_y);
+        Self::with_mode(world, Mode::Test { difficulty, start_y })
+    }
+
+    fn with_mode(world: World, mode: Mode) -> Box<Self> {
+        let bindings = load_bindings();
 
         set_cursor_grab(true);
 
         let camera = Camera2D::from_display_rect(Rect::new(0.0, 0.0, screen_width(), screen_height()));
-        let ground_position = 2000.0;
-
-        let scene = Self {
-            player: Player::new(vec2(screen_width() / 2.0, 10.0)),
-            cursor: Cursor::new(),
-            generator: Generator::new(difficulty),
-            fling_things: level_gen::generate_fling_things(ground_position),
-            birds: Vec::new(),
-            lightning: None,
-            time: 0.0,
-            ground_position,
-            mouse_captured: true,
-            end_timer: 0.0,
-            sounds: EnumMap::default(),
+
+        let mut scene = Self {
+            world,
+            mode,
+            accumulator: 0.0,
+            best_ghost: None,
+            ghost: Ghost::default(),
 
             camera,
-            inputs: EnumMap::default(),
+            input: FrameInput::default(),
             bindings,
         };
+        if !matches!(scene.mode, Mode::Test { .. }) {
+            scene.best_ghost = Ghost::load(&scene.difficulty().name, scene.world.seed);
+        }
         set_camera(&scene.camera);
         Box::new(scene)
     }
 
-    fn check_collisions(&mut self) {
-        for thing in self.fling_things.iter_mut() {
-            if thing.hitbox.overlaps(&self.player.hitbox) {
-                self.player.thing_collision(thing);
-                thing.collision();
-            }
-        }
-        for bird in self.birds.iter_mut() {
-            if bird.hitbox.overlaps(&self.player.hitbox) {
-                self.player.bird_collision(&mut self.sounds);
-                bird.collision();
-            }
-        }
-        for thing in self.fling_things.iter_mut() {
-            for bird in self.birds.iter_mut() {
-                if thing.flung() && bird.hitbox.overlaps(&thing.hitbox) {
-                    bird.collision();
-                    thing.collision();
-                }
-            }
-        }
-        if let Some(ref lightning) = self.lightning {
-            if lightning.collides_with(&self.player.hitbox) {
-                self.player.lightning_collision(&mut self.sounds);
-            }
+    fn difficulty(&self) -> &Difficulty {
+        match self.mode {
+            Mode::Live { ref recording } => &recording.difficulty,
+            Mode::Replay { ref replay, .. } => &replay.difficulty,
+            Mode::Test { ref difficulty, .. } => difficulty,
         }
     }
 
-    fn clean_up(&mut self) {
-        let play_zone = Rect::new(
-            -screen_width() / 2.0,
-            self.camera.target.y - screen_height(),
-            screen_width() * 2.0,
-            screen_height() * 2.0,
-        );
-
-        let mut remove = Vec::new();
-        for (i, bird) in self.birds.iter_mut().enumerate() {
-            if !bird.hitbox.overlaps(&play_zone) {
-                remove.push(i);
-            }
-        }
-        for i in remove.iter().rev() {
-            self.birds.swap_remove(*i);
-        }
-
-        if !self.cursor.has_selected() {
-            let mut remove = Vec::new();
-            for (i, thing) in self.fling_things.iter_mut().enumerate() {
-                if !thing.hitbox.overlaps(&play_zone) && thing.hitbox.y < self.camera.target.y || thing.should_destroy()
-                {
-                    remove.push(i);
-                }
-            }
-            for i in remove.iter().rev() {
-                self.fling_things.swap_remove(*i);
-            }
+    fn result(&self) -> RunResult {
+        RunResult {
+            time: self.world.time,
+            seed: self.world.seed,
+            difficulty: self.difficulty().name.clone(),
+            landing: self.world.landing(),
+            replayed: matches!(self.mode, Mode::Replay { .. }),
         }
+    }
 
-        if let Some(lightning) = &self.lightning {
-            if lightning.should_destroy() {
-                self.lightning = None;
-            }
-        }
+    fn alpha(&self) -> f32 {
+        self.accumulator / TIME_STEP
     }
 }
 
 impl Scene for GameScene {
     fn handle_input(&mut self) {
-        update_inputs(&mut self.inputs, &self.bindings);
-        if !self.mouse_captured && is_mouse_button_pressed(MouseButton::Left) {
-            self.mouse_captured = true;
-            set_cursor_grab(true);
-        }
-        if is_key_down(KeyCode::Escape) {
-            self.mouse_captured = false;
-            set_cursor_grab(false);
+        if let Mode::Live { .. } | Mode::Test { .. } = self.mode {
+            update_inputs(&mut self.input.inputs, &self.bindings);
+            let (x, y) = mouse_position();
+            self.input.cursor = self.world.screen_to_world(vec2(x, y));
+            self.input.mouse_pressed |= is_mouse_button_pressed(MouseButton::Left);
+            self.input.mouse_down = is_mouse_button_down(MouseButton::Left);
         }
     }
 
     fn update(&mut self, elapsed: f32) -> SceneAction {
-        if is_key_down(KeyCode::R) {
-            return SceneAction::Replace(MenuScene::new());
-        }
-
-        for (_, play) in self.sounds.iter_mut() {
-            *play = false;
-        }
-        if self.end_timer > 0.0 {
-            self.end_timer -= elapsed;
-            return if self.end_timer <= 0.0 {
-                SceneAction::Replace(EndScene::new(self.time))
-            } else {
-                SceneAction::Continue
+        if is_key_down(QUIT_KEY) {
+            return match self.mode {
+                Mode::Test { .. } => SceneAction::Pop,
+                _ => SceneAction::Replace(MenuScene::new()),
             };
         }
-        self.player.update(&self.inputs, &mut self.sounds, elapsed);
-        self.cursor
-            .update(&self.camera, &mut self.fling_things, &mut self.sounds);
-        for thing in self.fling_things.iter_mut() {
-            thing.update(elapsed);
-        }
-        for bird in self.birds.iter_mut() {
-            bird.update(&self.player, elapsed);
-        }
-
-        if self.ground_position + 60.0 > self.camera.target.y + screen_height() / 2.0 {
-            self.camera.target.y = self.player.position.y + screen_height() / 3.0;
-            set_camera(&self.camera);
-        }
-
-        self.generator.generate(
-            &self.camera,
-            &self.player,
-            &mut self.birds,
-            &mut self.lightning,
-            elapsed,
-        );
-
-        if let Some(ref mut lightning) = self.lightning {
-            lightning.update(&self.camera, &mut self.sounds, elapsed);
+        if is_key_pressed(PAUSE_KEY) {
+            // The frozen game keeps being drawn under the pause menu, so don't replay this frame's sounds
+            self.world.clear_sounds();
+            let run = match self.mode {
+                Mode::Test {
+                    ref difficulty,
+                    start_y,
+                } => PausedRun::Test {
+                    difficulty: difficulty.clone(),
+                    start_y,
+                },
+                _ => PausedRun::Live {
+                    difficulty: self.difficulty().clone(),
+                    seed: self.world.seed,
+                },
+            };
+            return SceneAction::Push(PauseScene::new(run));
         }
 
-        self.check_collisions();
+        self.world.clear_sounds();
+        self.accumulator += f32::min(elapsed, MAX_FRAME_TIME);
+        while self.accumulator >= TIME_STEP {
+            self.accumulator -= TIME_STEP;
+            let step = match self.mode {
+                Mode::Live { ref mut recording } => {
+                    recording.record(&self.input, TIME_STEP);
+                    TIME_STEP
+                }
+                Mode::Replay {
+                    ref replay,
+                    ref mut frame,
+                } => match replay.frames.get(*frame) {
+                    Some(recorded) => {
+                        self.input = recorded.input();
+                        *frame += 1;
+                        recorded.elapsed
+                    }
+                    None => return SceneAction::Replace(EndScene::new(self.result())),
+                },
+                Mode::Test { .. } => TIME_STEP,
+            };
 
-        if self.player.position.y + 50.0 >= self.ground_position {
-            self.player.land();
-            self.sounds[Sound::End] = true;
-            self.end_timer = 2.0;
-        } else {
-            self.time += elapsed;
+            self.world.update(&self.input, step);
+            self.ghost.record(self.world.time, &self.world.player);
+            // A click only counts once, even if several steps run in the same frame
+            self.input.mouse_pressed = false;
+
+            if self.world.finished() {
+                if let Mode::Live { ref recording } = self.mode {
+                    if let Err(e) = recording.save(REPLAY_PATH) {
+                        error!("{}", e);
+                    }
+                    if self.best_ghost.as_ref().is_none_or(|best| self.world.time > best.time) {
+                        self.ghost.time = self.world.time;
+                        if let Err(e) = self.ghost.save(&self.difficulty().name, self.world.seed) {
+                            error!("{}", e);
+                        }
+                    }
+                }
+                if let Mode::Test { .. } = self.mode {
+                    return SceneAction::Pop;
+                }
+                return SceneAction::Replace(EndScene::new(self.result()));
+            }
         }
 
-        self.clean_up();
+        self.camera.target.y = self.world.interpolated_camera_y(self.alpha());
         SceneAction::Continue
     }
 
     fn render(&self, assets: &mut Assets) {
-        draw_texture(assets.background, 0.0, -640.0, WHITE);
-        if let Some(ref lightning) = self.lightning {
-            lightning.draw(assets);
+        set_camera(&self.camera);
+        let alpha = self.alpha();
+        let tint = self.world.generator().bands().tint_at(self.camera.target.y);
+        draw_texture(assets.background, 0.0, -640.0, tint);
+        for zone in self.world.wind_zones.iter() {
+            zone.draw(self.world.time, self.world.viewport, self.camera.target.y);
+        }
+        for lightning in self.world.lightning.iter() {
+            lightning.draw(assets, alpha);
         }
-        if self.ground_position < self.camera.target.y + screen_height() / 2.0 {
+        if self.world.ground_position < self.camera.target.y + screen_height() / 2.0 {
             draw_texture(
                 assets.ground,
                 0.0,
-                self.ground_position,
+                self.world.ground_position,
                 Color::from_rgba(255, 255, 255, 255),
             );
         }
-        self.player.draw(assets);
-        for thing in self.fling_things.iter() {
-            thing.draw(assets);
+        if let Some(landing) = self.world.landing() {
+            let player = &self.world.player.hitbox;
+            landing.draw(player.x + player.w / 2.0, self.world.ground_position + 14.0, assets);
         }
-        for bird in self.birds.iter() {
-            bird.draw(assets);
+        for squid in self.world.squids.iter() {
+            squid.draw(alpha);
+        }
+        if let (Some(ghost), true) = (&self.best_ghost, config().show_ghost) {
+            ghost.draw(self.world.time, assets);
+        }
+        // When wrapping, draw everything that moves again a screen over on both sides, so it shows on both
+        // edges while crossing
+        let width = self.world.viewport.width;
+        let offsets: &[f32] = if self.world.viewport.wrap {
+            &[0.0, -width, width]
+        } else {
+            &[0.0]
+        };
+        for offset in offsets {
+            let mut camera = self.camera;
+            camera.target.x -= offset;
+            set_camera(&camera);
+            self.world.player.draw(assets, alpha);
+            for thing in self.world.fling_things.iter() {
+                thing.draw(assets, alpha);
+            }
+            // Birds still arriving are outside the screen on purpose, rather than crossing over
+            for bird in self
+                .world
+                .birds
+                .iter()
+                .filter(|bird| *offset == 0.0 || bird.in_flight())
+            {
+                bird.draw(assets, alpha);
+            }
+        }
+        set_camera(&self.camera);
+
+        for spawn in self.world.generator().upcoming() {
+            spawn.draw_warning(self.camera.target.y, self.world.viewport, assets);
         }
 
         let top_bar_pos = self.camera.screen_to_world(Vec2::new(0.0, 0.0));
@@ -251,7 +265,7 @@ impl Scene for GameScene {
 
         let text_pos = self.camera.screen_to_world(Vec2::new(screen_width() - 150.0, 30.0));
         draw_text_ex(
-            &format_time(self.time),
+            &format_time(self.world.time),
             text_pos.x,
             text_pos.y,
             TextParams {
@@ -261,29 +275,62 @@ impl Scene for GameScene {
             },
         );
 
+        // The full meter is drawn over the empty one, cut off at the current fuel level
         let fuel_pos = self.camera.screen_to_world(Vec2::new(15.0, 4.0));
-        let fuel_texture = match self.player.fuel {
-            0 => assets.meter_empty,
-            1 => assets.meter_1,
-            2 => assets.meter_2,
-            _ => assets.meter_full,
-        };
-        draw_texture(fuel_texture, fuel_pos.x, fuel_pos.y, WHITE);
+        let fill = self.world.player.fuel / MAX_FUEL;
+        let meter_size = vec2(assets.meter_full.width(), assets.meter_full.height());
+        draw_texture(assets.meter_empty, fuel_pos.x, fuel_pos.y, WHITE);
+        draw_texture_ex(
+            assets.meter_full,
+            fuel_pos.x,
+            fuel_pos.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(meter_size.x * fill, meter_size.y)),
+                source: Some(Rect::new(0.0, 0.0, meter_size.x * fill, meter_size.y)),
+                ..Default::default()
+            },
+        );
+
+        let player = &self.world.player.hitbox;
+        let wind_pos = self.camera.screen_to_world(Vec2::new(190.0, 20.0));
+        wind::draw_indicator(self.world.wind_at(player.y + player.h / 2.0), wind_pos);
+
+        if config().debug.fps {
+            let fps_pos = self.camera.screen_to_world(Vec2::new(10.0, 60.0));
+            draw_text(&format!("{} fps", get_fps()), fps_pos.x, fps_pos.y, 24.0, WHITE);
+        }
 
-        self.cursor.draw();
+        self.world.cursor.draw();
 
-        for (sound, play) in self.sounds.iter() {
+        for (sound, play) in self.world.sounds.iter() {
             if *play {
-                match sound {
-                    Sound::Hit => play_sound_once(assets.sfx_hit),
-                    Sound::Lightning => play_sound_once(assets.sfx_lightning),
-                    Sound::Fling => play_sound_once(assets.sfx_fling),
-                    Sound::Boost => play_sound_once(assets.sfx_boost),
-                    Sound::End => play_sound_once(assets.sfx_end),
-                }
+                let source = match sound {
+                    Sound::Hit => assets.sfx_hit,
+                    Sound::Lightning => assets.sfx_lightning,
+                    Sound::Fling => assets.sfx_fling,
+                    Sound::Boost => assets.sfx_boost,
+                    Sound::End => assets.sfx_end,
+                    Sound::Landing => match self.world.landing() {
+                        Some(landing) => landing.sound.source(assets),
+                        None => continue,
+                    },
+                };
+                play_sound(
+                    source,
+                    PlaySoundParams {
+                        looped: false,
+                        volume: config().master_volume * config().sound_volumes[sound],
+                    },
+                );
             }
         }
     }
+
+    fn resume(&mut self) {
+        set_cursor_grab(true);
+        self.bindings = load_bindings();
+    }
 }
 
 pub fn format_time(seconds: f32) -> String {
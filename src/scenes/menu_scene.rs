@@ -1,17 +1,29 @@
 use macroquad::prelude::*;
 
-use super::game_scene::GameScene;
+use super::{
+    bindings_scene::BindingsScene, editor_scene::EditorScene, game_scene::GameScene, scores_scene::ScoresScene,
+};
 use crate::{
     assets::Assets,
+    config::config,
     cursor::Cursor,
-    level_gen::Difficulty,
+    difficulty::{load_difficulties, Difficulty},
+    rng,
     scene::{Scene, SceneAction},
 };
 
 #[derive(Clone, Copy)]
 pub enum MenuAction {
-    StartGame,
-    StartGameHard,
+    StartGame(usize),
+    Controls,
+    HighScores,
+    Editor,
+    ResetBindings,
+    Resume,
+    Restart,
+    Settings,
+    Previous,
+    Next,
     Return,
     None,
 }
@@ -21,33 +33,105 @@ pub struct Button {
     pub action: MenuAction,
 }
 
+const VISIBLE_DIFFICULTIES: usize = 4;
+const DIFFICULTY_TOP: f32 = 170.0;
+const DIFFICULTY_SPACING: f32 = 75.0;
+const BUTTON_SCALE: f32 = 0.66;
+const BUTTON_SPRITE: (f32, f32) = (455.0, 106.0);
+const BUTTON_LABEL_X: f32 = 123.0;
+const BUTTON_LABEL_BASELINE: f32 = 75.0;
+/// Where the label of the Hard sprite ends, leaving the rest of it blank.
+const HARD_LABEL_END: f32 = 262.0;
+const BUTTON_LABEL_END: f32 = 430.0;
+const BUTTON_X: f32 = -70.0;
+const BUTTON_HOVER_X: f32 = -35.0;
+
 pub struct MenuScene {
     cursor: Cursor,
-    button: Button,
-    button_2: Button,
+    difficulties: Vec<Difficulty>,
+    buttons: Vec<Button>,
+    scroll: usize,
+    controls_button: Button,
+    scores_button: Button,
+    editor_button: Button,
+    seed: Option<u64>,
 }
 
 impl MenuScene {
     pub fn new() -> Box<Self> {
+        Self::with_seed(None)
+    }
+
+    pub fn with_seed(seed: Option<u64>) -> Box<Self> {
         set_cursor_grab(false);
         set_default_camera();
+        let difficulties = load_difficulties();
+        let buttons = (0..difficulties.len())
+            .map(|i| Button {
+                rect: Rect::new(
+                    BUTTON_X,
+                    0.0,
+                    BUTTON_SPRITE.0 * BUTTON_SCALE,
+                    BUTTON_SPRITE.1 * BUTTON_SCALE,
+                ),
+                action: MenuAction::StartGame(i),
+            })
+            .collect();
         Box::new(Self {
             cursor: Cursor::new(),
-            button: Button {
-                rect: Rect::new(-100.0, 200.0, 350.0, 100.0),
-                action: MenuAction::StartGame,
+            difficulties,
+            buttons,
+            scroll: 0,
+            controls_button: Button {
+                rect: Rect::new(25.0, 480.0, 200.0, 60.0),
+                action: MenuAction::Controls,
+            },
+            scores_button: Button {
+                rect: Rect::new(235.0, 480.0, 140.0, 60.0),
+                action: MenuAction::HighScores,
             },
-            button_2: Button {
-                rect: Rect::new(-100.0, 350.0, 350.0, 100.0),
-                action: MenuAction::StartGameHard,
+            editor_button: Button {
+                rect: Rect::new(25.0, 550.0, 350.0, 44.0),
+                action: MenuAction::Editor,
             },
+            seed,
         })
     }
+
+    fn next_seed(&self) -> u64 {
+        self.seed.unwrap_or_else(rng::random_seed)
+    }
+
+    fn visible_buttons(&self) -> impl Iterator<Item = &Button> {
+        self.buttons.iter().skip(self.scroll).take(VISIBLE_DIFFICULTIES)
+    }
+
+    fn start(&self, difficulty: usize) -> SceneAction {
+        SceneAction::Replace(GameScene::new(self.difficulties[difficulty].clone(), self.next_seed()))
+    }
 }
 
 impl Scene for MenuScene {
     fn handle_input(&mut self) {
         // update_inputs(&mut self.inputs, &self.bindings);
+        while let Some(c) = get_char_pressed() {
+            if let Some(digit) = c.to_digit(10) {
+                let seed = self.seed.unwrap_or(0);
+                if let Some(seed) = seed.checked_mul(10).and_then(|s| s.checked_add(digit as u64)) {
+                    self.seed = Some(seed);
+                }
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.seed = self.seed.map(|s| s / 10).filter(|s| *s > 0);
+        }
+        let (_, wheel) = mouse_wheel();
+        let max_scroll = self.buttons.len().saturating_sub(VISIBLE_DIFFICULTIES);
+        if wheel < 0.0 {
+            self.scroll = usize::min(self.scroll + 1, max_scroll);
+        } else if wheel > 0.0 {
+            self.scroll = self.scroll.saturating_sub(1);
+        }
     }
 
     fn update(&mut self, _elapsed: f32) -> SceneAction {
@@ -55,36 +139,157 @@ impl Scene for MenuScene {
         let mut action = MenuAction::None;
         let mut mouse_pos = Vec2::default();
         (mouse_pos.x, mouse_pos.y) = mouse_position();
-        self.button.rect.x = if self.button.rect.contains(mouse_pos) {
-            -50.0
-        } else {
-            -100.0
-        };
-        self.button_2.rect.x = if self.button_2.rect.contains(mouse_pos) {
-            -50.0
-        } else {
-            -100.0
-        };
+        for (i, button) in self.buttons.iter_mut().enumerate() {
+            button.rect.y = match i.checked_sub(self.scroll) {
+                Some(row) if row < VISIBLE_DIFFICULTIES => DIFFICULTY_TOP + row as f32 * DIFFICULTY_SPACING,
+                _ => -button.rect.h,
+            };
+            button.rect.x = if button.rect.contains(mouse_pos) {
+                BUTTON_HOVER_X
+            } else {
+                BUTTON_X
+            };
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            let default = self
+                .difficulties
+                .iter()
+                .position(|difficulty| difficulty.name == config().difficulty)
+                .unwrap_or(0);
+            return self.start(default);
+        }
         if is_mouse_button_pressed(MouseButton::Left) {
-            if self.button.rect.contains(mouse_pos) {
-                action = self.button.action;
+            for button in self.visible_buttons() {
+                if button.rect.contains(mouse_pos) {
+                    action = button.action;
+                }
+            }
+            if self.controls_button.rect.contains(mouse_pos) {
+                action = self.controls_button.action;
             }
-            if self.button_2.rect.contains(mouse_pos) {
-                action = self.button_2.action;
+            if self.scores_button.rect.contains(mouse_pos) {
+                action = self.scores_button.action;
+            }
+            if self.editor_button.rect.contains(mouse_pos) {
+                action = self.editor_button.action;
             }
         }
         match action {
-            MenuAction::StartGame => SceneAction::Replace(GameScene::new(Difficulty::Normal)),
-            MenuAction::StartGameHard => SceneAction::Replace(GameScene::new(Difficulty::Hard)),
+            MenuAction::StartGame(difficulty) => self.start(difficulty),
+            MenuAction::Controls => SceneAction::Replace(BindingsScene::new()),
+            MenuAction::HighScores => SceneAction::Replace(ScoresScene::new()),
+            MenuAction::Editor => SceneAction::Replace(EditorScene::new()),
             _ => SceneAction::Continue,
         }
     }
 
     fn render(&self, assets: &mut Assets) {
+        set_default_camera();
         draw_texture(assets.menu_bg, 0.0, 0.0, WHITE);
         draw_texture(assets.logo, 39.0, 20.0, WHITE);
-        draw_texture(assets.menu_button_n, self.button.rect.x, self.button.rect.y, WHITE);
-        draw_texture(assets.menu_button_h, self.button_2.rect.x, self.button_2.rect.y, WHITE);
+        for button in self.visible_buttons() {
+            if let MenuAction::StartGame(i) = button.action {
+                draw_difficulty_button(assets, button.rect, &self.difficulties[i].name);
+            }
+        }
+        for (button, label) in [(&self.controls_button, "Controls"), (&self.scores_button, "Scores")] {
+            let rect = button.rect;
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, GRAY);
+            draw_text_ex(
+                label,
+                rect.x + 20.0,
+                rect.y + 42.0,
+                TextParams {
+                    font: assets.font,
+                    font_size: 40,
+                    ..Default::default()
+                },
+            );
+        }
+        let rect = self.editor_button.rect;
+        draw_rectangle(rect.x, rect.y, rect.w, rect.h, GRAY);
+        draw_text_ex(
+            "Level Editor",
+            rect.x + 20.0,
+            rect.y + 32.0,
+            TextParams {
+                font: assets.font,
+                font_size: 32,
+                ..Default::default()
+            },
+        );
+        let seed_text = match self.seed {
+            Some(seed) => format!("Seed: {}", seed),
+            None => "Seed: random (type to set)".to_string(),
+        };
+        draw_text_ex(
+            &seed_text,
+            25.0,
+            625.0,
+            TextParams {
+                font: assets.font,
+                font_size: 24,
+                ..Default::default()
+            },
+        );
         self.cursor.draw();
     }
 }
+
+/// Draws a difficulty button from the button sprites. Normal and Hard have their own; the others get the blank
+/// part of the Hard one, stretched out, with their name written on it.
+fn draw_difficulty_button(assets: &Assets, rect: Rect, name: &str) {
+    let part = |texture, source: Rect, x: f32, w: f32| {
+        draw_texture_ex(
+            texture,
+            rect.x + x * BUTTON_SCALE,
+            rect.y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(w * BUTTON_SCALE, rect.h)),
+                source: Some(source),
+                ..Default::default()
+            },
+        );
+    };
+    let (width, height) = BUTTON_SPRITE;
+    match name {
+        "Normal" => part(assets.menu_button_n, Rect::new(0.0, 0.0, width, height), 0.0, width),
+        "Hard" => part(assets.menu_button_h, Rect::new(0.0, 0.0, width, height), 0.0, width),
+        _ => {
+            let blank_width = width - HARD_LABEL_END;
+            let left = Rect::new(0.0, 0.0, BUTTON_LABEL_X, height);
+            let middle = Rect::new(HARD_LABEL_END, 0.0, 1.0, height);
+            let right = Rect::new(HARD_LABEL_END, 0.0, blank_width, height);
+            part(assets.menu_button_h, left, 0.0, BUTTON_LABEL_X);
+            part(
+                assets.menu_button_h,
+                middle,
+                BUTTON_LABEL_X,
+                HARD_LABEL_END - BUTTON_LABEL_X,
+            );
+            part(assets.menu_button_h, right, HARD_LABEL_END, blank_width);
+
+            // Shrink long names to fit inside the button, the same size as the sprite labels otherwise
+            let label = name.to_uppercase();
+            let room = (BUTTON_LABEL_END - BUTTON_LABEL_X) * BUTTON_SCALE;
+            let full_size = (64.0 * BUTTON_SCALE) as u16;
+            let full_width = measure_text(&label, Some(assets.font), full_size, 1.0).width;
+            let font_size = if full_width > room {
+                (full_size as f32 * room / full_width) as u16
+            } else {
+                full_size
+            };
+            draw_text_ex(
+                &label,
+                rect.x + BUTTON_LABEL_X * BUTTON_SCALE,
+                rect.y + BUTTON_LABEL_BASELINE * BUTTON_SCALE,
+                TextParams {
+                    font: assets.font,
+                    font_size,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+}
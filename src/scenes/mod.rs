@@ -1,3 +1,24 @@
+pub mod bindings_scene;
+pub mod editor_scene;
 pub mod end_scece;
 pub mod game_scene;
 pub mod menu_scene;
+pub mod pause_scene;
+pub mod scores_scene;
+
+use macroquad::prelude::*;
+
+use crate::assets::Assets;
+
+pub fn draw_label(text: &str, x: f32, y: f32, font_size: u16, assets: &Assets) {
+    draw_text_ex(
+        text,
+        x,
+        y,
+        TextParams {
+            font: assets.font,
+            font_size,
+            ..Default::default()
+        },
+    );
+}
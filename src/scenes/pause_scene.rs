@@ -0,0 +1,130 @@
+use macroquad::prelude::*;
+
+use super::{
+    bindings_scene::BindingsScene,
+    game_scene::GameScene,
+    menu_scene::{Button, MenuAction, MenuScene},
+};
+use crate::{
+    assets::Assets,
+    cursor::Cursor,
+    difficulty::Difficulty,
+    scene::{Scene, SceneAction},
+};
+
+pub enum PausedRun {
+    Live { difficulty: Difficulty, seed: u64 },
+    Test { difficulty: Difficulty, start_y: f32 },
+}
+
+pub struct PauseScene {
+    cursor: Cursor,
+    buttons: [(Button, &'static str); 4],
+    run: PausedRun,
+}
+
+impl PauseScene {
+    pub fn new(run: PausedRun) -> Box<Self> {
+        set_cursor_grab(false);
+
+        let button = |row: f32, action| Button {
+            rect: Rect::new(75.0, 200.0 + row * 90.0, 250.0, 70.0),
+            action,
+        };
+        Box::new(Self {
+            cursor: Cursor::new(),
+            buttons: [
+                (button(0.0, MenuAction::Resume), "Resume"),
+                (button(1.0, MenuAction::Restart), "Restart"),
+                (button(2.0, MenuAction::Settings), "Controls"),
+                (button(3.0, MenuAction::Return), "Quit"),
+            ],
+            run,
+        })
+    }
+}
+
+impl Scene for PauseScene {
+    fn handle_input(&mut self) {}
+
+    fn update(&mut self, _elapsed: f32) -> SceneAction {
+        self.cursor.basic_update();
+        if is_key_pressed(KeyCode::Escape) {
+            return SceneAction::Pop;
+        }
+
+        let mut action = MenuAction::None;
+        if is_mouse_button_pressed(MouseButton::Left) {
+            let mut mouse_pos = Vec2::default();
+            (mouse_pos.x, mouse_pos.y) = mouse_position();
+            for (button, _) in self.buttons.iter() {
+                if button.rect.contains(mouse_pos) {
+                    action = button.action;
+                }
+            }
+        }
+        match action {
+            MenuAction::Resume => SceneAction::Pop,
+            MenuAction::Restart => match self.run {
+                PausedRun::Live { ref difficulty, seed } => {
+                    SceneAction::ReplaceAll(GameScene::new(difficulty.clone(), seed))
+                }
+                PausedRun::Test {
+                    ref difficulty,
+                    start_y,
+                } => SceneAction::ReplaceBoth(GameScene::test(difficulty.clone(), start_y)),
+            },
+            MenuAction::Settings => SceneAction::Push(BindingsScene::pushed()),
+            MenuAction::Return => match self.run {
+                PausedRun::Live { .. } => SceneAction::ReplaceAll(MenuScene::new()),
+                PausedRun::Test { .. } => SceneAction::PopBoth,
+            },
+            _ => SceneAction::Continue,
+        }
+    }
+
+    fn render(&self, assets: &mut Assets) {
+        set_default_camera();
+        draw_rectangle(
+            0.0,
+            0.0,
+            screen_width(),
+            screen_height(),
+            Color::from_rgba(0, 0, 0, 160),
+        );
+        draw_rectangle(0.0, 75.0, screen_width(), 100.0, Color::from_rgba(196, 84, 91, 255));
+        draw_text_ex(
+            "Paused",
+            50.0,
+            150.0,
+            TextParams {
+                font: assets.font,
+                font_size: 72,
+                ..Default::default()
+            },
+        );
+        for (button, label) in self.buttons.iter() {
+            let rect = button.rect;
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, GRAY);
+            draw_text_ex(
+                label,
+                rect.x + 30.0,
+                rect.y + 48.0,
+                TextParams {
+                    font: assets.font,
+                    font_size: 40,
+                    ..Default::default()
+                },
+            );
+        }
+        self.cursor.draw();
+    }
+
+    fn resume(&mut self) {
+        set_cursor_grab(false);
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
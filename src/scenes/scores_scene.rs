@@ -0,0 +1,105 @@
+use macroquad::prelude::*;
+
+use super::{
+    draw_label,
+    menu_scene::{Button, MenuAction, MenuScene},
+};
+use crate::{
+    assets::Assets,
+    cursor::Cursor,
+    difficulty::load_difficulties,
+    scene::{Scene, SceneAction},
+    scores::{draw_scores, HighScores},
+};
+
+pub struct ScoresScene {
+    cursor: Cursor,
+    scores: HighScores,
+    difficulties: Vec<String>,
+    selected: usize,
+    buttons: [Button; 3],
+}
+
+impl ScoresScene {
+    pub fn new() -> Box<Self> {
+        set_cursor_grab(false);
+        set_default_camera();
+
+        let scores = HighScores::load();
+        let mut difficulties: Vec<String> = load_difficulties().into_iter().map(|d| d.name).collect();
+        for name in scores.difficulties() {
+            if !difficulties.contains(name) {
+                difficulties.push(name.clone());
+            }
+        }
+
+        Box::new(Self {
+            cursor: Cursor::new(),
+            scores,
+            difficulties,
+            selected: 0,
+            buttons: [
+                Button {
+                    rect: Rect::new(25.0, 100.0, 50.0, 50.0),
+                    action: MenuAction::Previous,
+                },
+                Button {
+                    rect: Rect::new(325.0, 100.0, 50.0, 50.0),
+                    action: MenuAction::Next,
+                },
+                Button {
+                    rect: Rect::new(25.0, 515.0, 350.0, 100.0),
+                    action: MenuAction::Return,
+                },
+            ],
+        })
+    }
+}
+
+impl Scene for ScoresScene {
+    fn handle_input(&mut self) {}
+
+    fn update(&mut self, _elapsed: f32) -> SceneAction {
+        self.cursor.basic_update();
+        let mut action = MenuAction::None;
+        if is_mouse_button_pressed(MouseButton::Left) {
+            let mut mouse_pos = Vec2::default();
+            (mouse_pos.x, mouse_pos.y) = mouse_position();
+            for button in self.buttons.iter() {
+                if button.rect.contains(mouse_pos) {
+                    action = button.action;
+                }
+            }
+        }
+        let count = self.difficulties.len();
+        match action {
+            MenuAction::Previous => self.selected = (self.selected + count - 1) % count,
+            MenuAction::Next => self.selected = (self.selected + 1) % count,
+            MenuAction::Return => return SceneAction::Replace(MenuScene::new()),
+            _ => {}
+        }
+        SceneAction::Continue
+    }
+
+    fn render(&self, assets: &mut Assets) {
+        set_default_camera();
+        clear_background(Color::from_rgba(52, 62, 65, 255));
+        draw_rectangle(0.0, 20.0, screen_width(), 60.0, Color::from_rgba(196, 84, 91, 255));
+        draw_label("High Scores", 25.0, 65.0, 48, assets);
+
+        for (button, label) in self.buttons.iter().zip(["<", ">", "Return to Menu"]) {
+            let rect = button.rect;
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, GRAY);
+            let offset = if rect.w > rect.h {
+                vec2(35.0, 61.0)
+            } else {
+                vec2(15.0, 36.0)
+            };
+            draw_label(label, rect.x + offset.x, rect.y + offset.y, 40, assets);
+        }
+        let difficulty = &self.difficulties[self.selected];
+        draw_label(difficulty, 95.0, 136.0, 32, assets);
+        draw_scores(self.scores.table(difficulty), 190.0, None, None, assets);
+        self.cursor.draw();
+    }
+}
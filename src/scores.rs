@@ -0,0 +1,154 @@
+use std::{collections::BTreeMap, fs, io::ErrorKind};
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{assets::Assets, scenes::game_scene::format_time};
+
+pub const SCORES_PATH: &str = "scores.ron";
+pub const MAX_SCORES: usize = 10;
+const ROW_HEIGHT: f32 = 21.0;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Score {
+    pub name: String,
+    pub time: f32,
+    pub seed: u64,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct HighScores {
+    tables: BTreeMap<String, Vec<Score>>,
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        let text = match fs::read_to_string(SCORES_PATH) {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                error!("Could not read {}: {}", SCORES_PATH, e);
+                return Self::default();
+            }
+        };
+        ron::from_str(&text).unwrap_or_else(|e| {
+            error!("Ignoring invalid {}: {}", SCORES_PATH, e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+        fs::write(SCORES_PATH, text).map_err(|e| format!("could not write {}: {}", SCORES_PATH, e))
+    }
+
+    pub fn table(&self, difficulty: &str) -> &[Score] {
+        self.tables.get(difficulty).map_or(&[], |scores| scores.as_slice())
+    }
+
+    pub fn difficulties(&self) -> impl Iterator<Item = &String> {
+        self.tables.keys()
+    }
+
+    pub fn rank(&self, difficulty: &str, time: f32) -> Option<usize> {
+        let rank = self
+            .table(difficulty)
+            .iter()
+            .take_while(|score| score.time >= time)
+            .count();
+        (rank < MAX_SCORES).then_some(rank)
+    }
+
+    pub fn insert(&mut self, difficulty: &str, score: Score) {
+        if let Some(rank) = self.rank(difficulty, score.time) {
+            let table = self.tables.entry(difficulty.to_string()).or_default();
+            table.insert(rank, score);
+            table.truncate(MAX_SCORES);
+        }
+    }
+}
+
+pub fn draw_scores(scores: &[Score], y: f32, highlight: Option<usize>, entry: Option<&str>, assets: &Assets) {
+    let mut rows: Vec<(String, String)> = scores
+        .iter()
+        .map(|score| (score.name.clone(), format_time(score.time)))
+        .collect();
+    if let (Some(rank), Some(name)) = (highlight, entry) {
+        rows.insert(rank, (format!("{}_", name), String::new()));
+        rows.truncate(MAX_SCORES);
+    }
+    if rows.is_empty() {
+        draw_row("No times yet", "", y, WHITE, assets);
+    }
+    for (i, (name, time)) in rows.iter().enumerate() {
+        let row_y = y + i as f32 * ROW_HEIGHT;
+        let color = if highlight == Some(i) {
+            Color::from_rgba(196, 84, 91, 255)
+        } else {
+            WHITE
+        };
+        draw_row(&format!("{:>2}. {}", i + 1, name), time, row_y, color, assets);
+    }
+}
+
+fn draw_row(left: &str, right: &str, y: f32, color: Color, assets: &Assets) {
+    for (text, x) in [(left, 25.0), (right, 275.0)] {
+        draw_text_ex(
+            text,
+            x,
+            y,
+            TextParams {
+                font: assets.font,
+                font_size: 20,
+                color,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(time: f32) -> Score {
+        Score {
+            name: format!("{}", time),
+            time,
+            seed: 0,
+        }
+    }
+
+    fn times(scores: &HighScores, difficulty: &str) -> Vec<f32> {
+        scores.table(difficulty).iter().map(|score| score.time).collect()
+    }
+
+    #[test]
+    fn longer_times_rank_higher() {
+        let mut scores = HighScores::default();
+        assert_eq!(scores.rank("Normal", 10.0), Some(0));
+        for time in [20.0, 40.0, 30.0] {
+            scores.insert("Normal", score(time));
+        }
+        assert_eq!(times(&scores, "Normal"), [40.0, 30.0, 20.0]);
+        assert_eq!(scores.rank("Normal", 35.0), Some(1));
+        // A tie goes below the time that was there first
+        assert_eq!(scores.rank("Normal", 30.0), Some(2));
+        assert!(scores.table("Hard").is_empty());
+    }
+
+    #[test]
+    fn tables_keep_only_the_best() {
+        let mut scores = HighScores::default();
+        for i in 0..MAX_SCORES + 3 {
+            scores.insert("Normal", score(i as f32));
+        }
+        let table = times(&scores, "Normal");
+        assert_eq!(table.len(), MAX_SCORES);
+        assert_eq!(table[0], (MAX_SCORES + 2) as f32);
+        assert_eq!(scores.rank("Normal", 0.5), None);
+        scores.insert("Normal", score(0.5));
+        assert_eq!(times(&scores, "Normal"), table);
+    }
+}
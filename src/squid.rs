@@ -0,0 +1,141 @@
+use macroquad::prelude::*;
+
+use crate::{draw_hitbox, world::Viewport};
+
+pub const SQUID_DIM: (f32, f32) = (48.0, 72.0);
+const RISE_SPEED: f32 = 25.0;
+const SWAY_SPEED: f32 = 30.0;
+const CARRY_SPEED: f32 = 60.0;
+const CARRY_TIME: f32 = 3.0;
+const LEAVE_SPEED: f32 = 200.0;
+
+enum State {
+    Drifting,
+    Carrying { timer: f32 },
+    Leaving,
+    Knocked,
+}
+
+pub struct Squid {
+    state: State,
+    position: Vec2,
+    last_position: Vec2,
+    velocity: Vec2,
+    pub hitbox: Rect,
+    animation_timer: f32,
+}
+
+impl Squid {
+    pub fn spawn(x_pos: f32, y_pos: f32) -> Self {
+        let position = vec2(x_pos, y_pos);
+        let mut squid = Self {
+            state: State::Drifting,
+            position,
+            last_position: position,
+            velocity: vec2(0.0, -RISE_SPEED),
+            hitbox: Rect::new(x_pos, y_pos, 40.0, 56.0),
+            animation_timer: 0.0,
+        };
+        squid.update_hitbox();
+        squid
+    }
+
+    pub fn update(&mut self, viewport: Viewport, elapsed: f32) {
+        self.last_position = self.position;
+        self.animation_timer += elapsed;
+        match self.state {
+            State::Drifting => {
+                self.velocity.x = f32::sin(self.animation_timer) * SWAY_SPEED;
+                if self.position.x < 0.0 {
+                    self.velocity.x = SWAY_SPEED;
+                } else if self.position.x > viewport.width - SQUID_DIM.0 {
+                    self.velocity.x = -SWAY_SPEED;
+                }
+            }
+            State::Carrying { ref mut timer } => {
+                *timer -= elapsed;
+                if *timer < 0.0 {
+                    self.release();
+                }
+            }
+            State::Leaving | State::Knocked => {}
+        }
+        self.position += self.velocity * elapsed;
+        self.update_hitbox();
+    }
+
+    pub fn draw(&self, alpha: f32) {
+        let position = self.last_position.lerp(self.position, alpha);
+        let (body, tentacle) = match self.state {
+            State::Knocked => (
+                Color::from_rgba(150, 120, 160, 255),
+                Color::from_rgba(120, 95, 130, 255),
+            ),
+            _ => (
+                Color::from_rgba(216, 124, 200, 255),
+                Color::from_rgba(180, 92, 170, 255),
+            ),
+        };
+        let center_x = position.x + SQUID_DIM.0 / 2.0;
+
+        // Tentacles wave faster while pulling
+        let wave_speed = match self.state {
+            State::Carrying { .. } | State::Leaving => 14.0,
+            _ => 5.0,
+        };
+        for i in 0..4 {
+            let base_x = position.x + 9.0 + i as f32 * 10.0;
+            let wave = f32::sin(self.animation_timer * wave_speed + i as f32) * 5.0;
+            let mid = vec2(base_x + wave, position.y + 52.0);
+            let tip = vec2(base_x - wave, position.y + SQUID_DIM.1);
+            draw_line(base_x, position.y + 34.0, mid.x, mid.y, 4.0, tentacle);
+            draw_line(mid.x, mid.y, tip.x, tip.y, 3.0, tentacle);
+        }
+
+        draw_triangle(
+            vec2(center_x, position.y),
+            vec2(position.x + 4.0, position.y + 26.0),
+            vec2(position.x + SQUID_DIM.0 - 4.0, position.y + 26.0),
+            body,
+        );
+        draw_circle(center_x, position.y + 28.0, 18.0, body);
+        draw_circle(center_x - 7.0, position.y + 28.0, 4.0, WHITE);
+        draw_circle(center_x + 7.0, position.y + 28.0, 4.0, WHITE);
+        draw_circle(center_x - 7.0, position.y + 29.0, 2.0, BLACK);
+        draw_circle(center_x + 7.0, position.y + 29.0, 2.0, BLACK);
+        draw_hitbox(&self.hitbox);
+    }
+
+    fn update_hitbox(&mut self) {
+        self.hitbox.x = self.position.x + (SQUID_DIM.0 - self.hitbox.w) / 2.0;
+        self.hitbox.y = self.position.y + (SQUID_DIM.1 - self.hitbox.h) / 2.0;
+    }
+
+    pub fn can_grab(&self) -> bool {
+        matches!(self.state, State::Drifting)
+    }
+
+    pub fn grab(&mut self) -> Vec2 {
+        self.state = State::Carrying { timer: CARRY_TIME };
+        self.velocity = vec2(0.0, -CARRY_SPEED);
+        self.velocity
+    }
+
+    pub fn carrying(&self) -> bool {
+        matches!(self.state, State::Carrying { .. })
+    }
+
+    pub fn release(&mut self) {
+        self.state = State::Leaving;
+        self.velocity = vec2(0.0, -LEAVE_SPEED);
+    }
+
+    pub fn knock(&mut self, velocity: Vec2) {
+        self.state = State::Knocked;
+        self.velocity = velocity;
+    }
+
+    pub fn knocked(&self) -> bool {
+        matches!(self.state, State::Knocked)
+    }
+}
@@ -0,0 +1,104 @@
+use std::fs;
+
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{bird::BirdKind, fling::FlingKind, lightning::LightningPattern, HDirection};
+
+pub const SCRIPT_DIR: &str = "scripts";
+
+const BUILT_IN: [(&str, &str); 1] = [("gauntlet", include_str!("../assets/scripts/gauntlet.ron"))];
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum Trigger {
+    Time(f32),
+    Depth(f32),
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum WaveSpawn {
+    Bird {
+        side: HDirection,
+        height: f32,
+        kind: BirdKind,
+    },
+    Lightning {
+        offset: f32,
+        pattern: LightningPattern,
+    },
+    Cloud {
+        kind: FlingKind,
+        x: f32,
+        y: f32,
+    },
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WaveEvent {
+    pub at: Trigger,
+    pub spawn: WaveSpawn,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WaveScript {
+    #[serde(default)]
+    pub random_hazards: bool,
+    #[serde(default = "default_random_clouds")]
+    pub random_clouds: bool,
+    #[serde(default)]
+    pub level_depth: Option<f32>,
+    pub events: Vec<WaveEvent>,
+}
+
+fn default_random_clouds() -> bool {
+    true
+}
+
+impl Trigger {
+    pub fn reached(self, time: f32, depth: f32) -> bool {
+        match self {
+            Trigger::Time(at) => time >= at,
+            Trigger::Depth(at) => depth >= at,
+        }
+    }
+}
+
+impl WaveScript {
+    pub fn load(name: &str) -> Result<Self, String> {
+        let text = match BUILT_IN.iter().find(|(built_in, _)| *built_in == name) {
+            Some((_, text)) => text.to_string(),
+            None => fs::read_to_string(format!("{}/{}.ron", SCRIPT_DIR, name)).map_err(|e| e.to_string())?,
+        };
+        ron::from_str(&text).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self, name: &str) -> Result<(), String> {
+        fs::create_dir_all(SCRIPT_DIR).map_err(|e| format!("could not create {}: {}", SCRIPT_DIR, e))?;
+        let path = format!("{}/{}.ron", SCRIPT_DIR, name);
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+        fs::write(&path, text).map_err(|e| format!("could not write {}: {}", path, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn triggers_reached_once_passed() {
+        assert!(!Trigger::Time(3.0).reached(2.9, 1000.0));
+        assert!(Trigger::Time(3.0).reached(3.0, 0.0));
+        assert!(!Trigger::Depth(600.0).reached(100.0, 599.0));
+        assert!(Trigger::Depth(600.0).reached(0.0, 650.0));
+    }
+
+    #[test]
+    fn built_in_scripts_load() {
+        for (name, _) in BUILT_IN {
+            let script = WaveScript::load(name).unwrap();
+            assert!(!script.events.is_empty());
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use macroquad::prelude::*;
+
+use crate::world::Viewport;
+
+const STREAK_AREA: f32 = 1500.0;
+const INDICATOR_MAX: f32 = 60.0;
+
+pub struct WindZone {
+    pub top: f32,
+    pub height: f32,
+    pub force: Vec2,
+}
+
+impl WindZone {
+    pub fn new(top: f32, height: f32, force: Vec2) -> Self {
+        Self { top, height, force }
+    }
+
+    pub fn contains(&self, y: f32) -> bool {
+        y >= self.top && y < self.top + self.height
+    }
+
+    pub fn draw(&self, time: f32, viewport: Viewport, camera_y: f32) {
+        let top = f32::max(self.top, camera_y - viewport.height / 2.0);
+        let bottom = f32::min(self.top + self.height, camera_y + viewport.height / 2.0);
+        if top >= bottom {
+            return;
+        }
+
+        let strength = self.force.length();
+        let dir = self.force.normalize_or_zero();
+        let length = 10.0 + strength * 0.5;
+        let offset = dir * (100.0 + strength * 3.0) * time;
+        let count = (self.height * viewport.width / STREAK_AREA) as usize;
+        for i in 0..count {
+            // Evenly scattered fixed spots in the zone, scrolled along the wind and wrapped around
+            let x = ((i as f32 * 0.754_877_7).fract() * viewport.width + offset.x).rem_euclid(viewport.width);
+            let y = self.top + ((i as f32 * 0.569_840_3).fract() * self.height + offset.y).rem_euclid(self.height);
+            if y < top || y > bottom {
+                continue;
+            }
+            draw_line(
+                x,
+                y,
+                x - dir.x * length,
+                y - dir.y * length,
+                1.5,
+                Color::from_rgba(255, 255, 255, 80),
+            );
+        }
+    }
+}
+
+pub fn force_at(zones: &[WindZone], y: f32) -> Vec2 {
+    zones
+        .iter()
+        .filter(|zone| zone.contains(y))
+        .map(|zone| &zone.force)
+        .sum()
+}
+
+pub fn draw_indicator(force: Vec2, center: Vec2) {
+    let strength = f32::min(force.length() / INDICATOR_MAX, 1.0);
+    if strength <= 0.0 {
+        draw_circle_lines(center.x, center.y, 4.0, 1.5, GRAY);
+        return;
+    }
+    let dir = force.normalize();
+    let half = dir * (6.0 + strength * 10.0);
+    let color = Color::new(1.0, 1.0, 1.0, 0.4 + strength * 0.6);
+    let (start, end) = (center - half, center + half);
+    draw_line(start.x, start.y, end.x, end.y, 3.0, color);
+    let side = vec2(-dir.y, dir.x) * 6.0;
+    draw_triangle(end + dir * 6.0, end + side, end - side, color);
+}
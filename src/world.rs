@@ -0,0 +1,485 @@
+use enum_map::{Enum, EnumMap};
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bird::Bird,
+    cursor::Cursor,
+    difficulty::Difficulty,
+    fling::{self, FlingKind, FlingThing, Physics},
+    landing::{self, Landing},
+    level_gen::{self, Generator},
+    lightning::Lightning,
+    player::Player,
+    rng::Rng,
+    squid::Squid,
+    wind::{self, WindZone},
+};
+
+pub const TIME_STEP: f32 = 1.0 / 120.0;
+const END_DELAY: f32 = 2.0;
+
+#[derive(Enum, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Input {
+    Up,
+    Down,
+    Left,
+    Right,
+    BoostLeft,
+    BoostRight,
+}
+
+#[derive(Enum, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum Sound {
+    Hit,
+    Lightning,
+    Fling,
+    Boost,
+    End,
+    Landing,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Viewport {
+    pub width: f32,
+    pub height: f32,
+    pub wrap: bool,
+}
+
+impl Viewport {
+    pub fn screen() -> Self {
+        Self {
+            width: screen_width(),
+            height: screen_height(),
+            wrap: false,
+        }
+    }
+
+    pub fn wrap_shift(&self, x: f32, size: f32) -> f32 {
+        let center = x + size / 2.0;
+        if !self.wrap {
+            0.0
+        } else if center < 0.0 {
+            self.width
+        } else if center >= self.width {
+            -self.width
+        } else {
+            0.0
+        }
+    }
+
+    pub fn overlaps(&self, a: &Rect, b: &Rect) -> bool {
+        self.wrapped(a).iter().any(|a| a.overlaps(b))
+    }
+
+    pub fn wrapped(&self, rect: &Rect) -> Vec<Rect> {
+        if self.wrap {
+            vec![
+                *rect,
+                rect.offset(vec2(-self.width, 0.0)),
+                rect.offset(vec2(self.width, 0.0)),
+            ]
+        } else {
+            vec![*rect]
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct FrameInput {
+    pub inputs: EnumMap<Input, bool>,
+    pub cursor: Vec2,
+    pub mouse_pressed: bool,
+    pub mouse_down: bool,
+}
+
+pub struct World {
+    pub player: Player,
+    pub cursor: Cursor,
+    generator: Generator,
+    pub fling_things: Vec<FlingThing>,
+    physics: Physics,
+    pub birds: Vec<Bird>,
+    pub lightning: Vec<Lightning>,
+    pub squids: Vec<Squid>,
+    pub wind_zones: Vec<WindZone>,
+    pub time: f32,
+    pub ground_position: f32,
+    pub camera_y: f32,
+    last_camera_y: f32,
+    pub viewport: Viewport,
+    pub sounds: EnumMap<Sound, bool>,
+    pub seed: u64,
+    rng: Rng,
+    end_timer: f32,
+    finished: bool,
+    landing: Option<&'static Landing>,
+}
+
+impl World {
+    pub fn new(difficulty: &Difficulty, viewport: Viewport, seed: u64) -> Self {
+        let viewport = Viewport {
+            wrap: difficulty.wrap_around,
+            ..viewport
+        };
+        let mut rng = Rng::new(seed);
+        let script = difficulty.waves.clone();
+        // A script can move the ground, which everything laid out down the level depends on
+        let difficulty = &Difficulty {
+            level_depth: script
+                .as_ref()
+                .and_then(|script| script.level_depth)
+                .unwrap_or(difficulty.level_depth),
+            ..difficulty.clone()
+        };
+        let fling_things = match script {
+            Some(ref script) if !script.random_clouds => Vec::new(),
+            _ => level_gen::generate_fling_things(difficulty, viewport, &mut rng),
+        };
+        let wind_zones = level_gen::generate_wind_zones(difficulty, viewport, &mut rng);
+
+        Self {
+            player: Player::new(
+                vec2(viewport.width / 2.0, 10.0),
+                difficulty.balloons,
+                difficulty.max_balloons,
+                difficulty.fuel,
+                difficulty.fuel_recharge,
+            ),
+            cursor: Cursor::at(Vec2::default()),
+            generator: Generator::new(difficulty, script),
+            fling_things,
+            physics: Physics {
+                drag: difficulty.cloud_drag,
+                gravity: difficulty.cloud_gravity,
+            },
+            birds: Vec::new(),
+            lightning: Vec::new(),
+            squids: Vec::new(),
+            wind_zones,
+            time: 0.0,
+            ground_position: difficulty.level_depth,
+            camera_y: viewport.height / 2.0,
+            last_camera_y: viewport.height / 2.0,
+            viewport,
+            sounds: EnumMap::default(),
+            seed,
+            rng,
+            end_timer: 0.0,
+            finished: false,
+            landing: None,
+        }
+    }
+
+    /// Advances the simulation by `elapsed` seconds. Sounds accumulate until `clear_sounds` is called.
+    pub fn update(&mut self, input: &FrameInput, elapsed: f32) {
+        self.last_camera_y = self.camera_y;
+        if self.end_timer > 0.0 {
+            self.end_timer -= elapsed;
+            self.finished = self.end_timer <= 0.0;
+            return;
+        }
+        let wind = self.wind_at(self.player.hitbox.y + self.player.hitbox.h / 2.0);
+        self.player
+            .update(&input.inputs, wind, &mut self.sounds, self.viewport, elapsed);
+        self.cursor.update(
+            input,
+            &mut self.fling_things,
+            &self.physics,
+            &self.wind_zones,
+            self.viewport,
+            &mut self.sounds,
+        );
+        for thing in self.fling_things.iter_mut() {
+            thing.update(&self.physics, &self.wind_zones, self.viewport, elapsed);
+        }
+        for bird in self.birds.iter_mut() {
+            bird.push(
+                wind::force_at(&self.wind_zones, bird.hitbox.y + bird.hitbox.h / 2.0),
+                elapsed,
+            );
+            bird.update(&self.player, self.viewport, &mut self.rng, elapsed);
+        }
+        for squid in self.squids.iter_mut() {
+            squid.update(self.viewport, elapsed);
+        }
+        // The player and the squid carrying them let go of each other together
+        if !self.player.carried() {
+            for squid in self.squids.iter_mut().filter(|squid| squid.carrying()) {
+                squid.release();
+            }
+        } else if !self.squids.iter().any(|squid| squid.carrying()) {
+            self.player.release();
+        }
+
+        self.camera_y = self.camera_target();
+
+        self.generator.generate(
+            self.camera_y,
+            self.viewport,
+            &self.player,
+            &mut self.birds,
+            &mut self.lightning,
+            &mut self.squids,
+            &mut self.fling_things,
+            &mut self.rng,
+            elapsed,
+        );
+
+        for lightning in self.lightning.iter_mut() {
+            lightning.update(self.camera_y, self.viewport, &self.player, &mut self.sounds, elapsed);
+        }
+
+        self.check_collisions();
+
+        if self.player.position.y + 50.0 >= self.ground_position {
+            self.player.land();
+            let landing = landing::choose(self.time, self.player.balloons());
+            self.landing = Some(landing);
+            self.sounds[Sound::End] = true;
+            self.sounds[Sound::Landing] = true;
+            self.end_timer = END_DELAY;
+        } else {
+            self.time += elapsed;
+        }
+
+        self.clean_up();
+    }
+
+    pub fn clear_sounds(&mut self) {
+        for (_, play) in self.sounds.iter_mut() {
+            *play = false;
+        }
+    }
+
+    pub fn interpolated_camera_y(&self, alpha: f32) -> f32 {
+        self.last_camera_y + (self.camera_y - self.last_camera_y) * alpha
+    }
+
+    pub fn generator(&self) -> &Generator {
+        &self.generator
+    }
+
+    pub fn wind_at(&self, y: f32) -> Vec2 {
+        wind::force_at(&self.wind_zones, y)
+    }
+
+    fn camera_target(&self) -> f32 {
+        f32::min(
+            self.player.position.y + self.viewport.height / 3.0,
+            self.ground_position + 60.0 - self.viewport.height / 2.0,
+        )
+    }
+
+    pub fn start_at(&mut self, y: f32) {
+        let y = y.clamp(10.0, self.ground_position - 200.0);
+        self.player.teleport(vec2(self.player.position.x, y));
+        self.camera_y = self.camera_target();
+        self.last_camera_y = self.camera_y;
+        self.generator.skip_to(y);
+    }
+
+    pub fn landing(&self) -> Option<&'static Landing> {
+        self.landing
+    }
+
+    pub fn finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn screen_to_world(&self, point: Vec2) -> Vec2 {
+        vec2(point.x, point.y + self.camera_y - self.viewport.height / 2.0)
+    }
+
+    fn check_collisions(&mut self) {
+        let viewport = self.viewport;
+        for thing in self.fling_things.iter_mut() {
+            if viewport.overlaps(&thing.hitbox, &self.player.hitbox) {
+                self.player.thing_collision(thing);
+                thing.collision();
+            }
+        }
+        for bird in self.birds.iter_mut() {
+            // Birds still arriving are outside the screen on purpose, rather than crossing over
+            let bird_viewport = Viewport {
+                wrap: viewport.wrap && bird.in_flight(),
+                ..viewport
+            };
+            if bird_viewport.overlaps(&bird.hitbox, &self.player.hitbox) {
+                self.player.bird_collision(&mut self.sounds);
+                bird.collision();
+            }
+        }
+        self.check_thing_collisions();
+        for thing in self.fling_things.iter_mut() {
+            for bird in self.birds.iter_mut() {
+                if thing.flung() && viewport.overlaps(&thing.hitbox, &bird.hitbox) {
+                    bird.collision();
+                    thing.collision();
+                }
+            }
+        }
+        for squid in self.squids.iter_mut() {
+            if squid.can_grab() && self.player.can_grab() && viewport.overlaps(&squid.hitbox, &self.player.hitbox) {
+                self.player.grab(squid.grab());
+            }
+            for thing in self.fling_things.iter_mut() {
+                if thing.flung() && !squid.knocked() && viewport.overlaps(&squid.hitbox, &thing.hitbox) {
+                    if squid.carrying() {
+                        self.player.release();
+                    }
+                    squid.knock(thing.velocity);
+                    thing.collision();
+                }
+            }
+        }
+        for lightning in self.lightning.iter() {
+            if viewport
+                .wrapped(&self.player.hitbox)
+                .iter()
+                .any(|hitbox| lightning.collides_with(hitbox))
+            {
+                self.player.lightning_collision(&mut self.sounds);
+            }
+        }
+    }
+
+    fn check_thing_collisions(&mut self) {
+        for j in 1..self.fling_things.len() {
+            let (before, after) = self.fling_things.split_at_mut(j);
+            let b = &mut after[0];
+            for a in before.iter_mut() {
+                if a.should_destroy()
+                    || b.should_destroy()
+                    || !(a.flung() || b.flung())
+                    || !self.viewport.overlaps(&a.hitbox, &b.hitbox)
+                {
+                    continue;
+                }
+                let gold = if !a.flung() && matches!(a.kind, FlingKind::GoldCloud) {
+                    Some(&mut *a)
+                } else if !b.flung() && matches!(b.kind, FlingKind::GoldCloud) {
+                    Some(&mut *b)
+                } else {
+                    None
+                };
+                match gold {
+                    Some(gold) => {
+                        self.player.thing_collision(gold);
+                        gold.collision();
+                    }
+                    None => fling::bounce(a, b, self.viewport),
+                }
+            }
+        }
+    }
+
+    fn clean_up(&mut self) {
+        let play_zone = Rect::new(
+            -self.viewport.width / 2.0,
+            self.camera_y - self.viewport.height,
+            self.viewport.width * 2.0,
+            self.viewport.height * 2.0,
+        );
+
+        let mut remove = Vec::new();
+        for (i, bird) in self.birds.iter_mut().enumerate() {
+            if !bird.hitbox.overlaps(&play_zone) {
+                remove.push(i);
+            }
+        }
+        for i in remove.iter().rev() {
+            self.birds.swap_remove(*i);
+        }
+        self.squids
+            .retain(|squid| squid.carrying() || squid.hitbox.overlaps(&play_zone));
+
+        if !self.cursor.has_selected() {
+            let mut remove = Vec::new();
+            for (i, thing) in self.fling_things.iter_mut().enumerate() {
+                if !thing.hitbox.overlaps(&play_zone) && thing.hitbox.y < self.camera_y || thing.should_destroy() {
+                    remove.push(i);
+                }
+            }
+            for i in remove.iter().rev() {
+                self.fling_things.swap_remove(*i);
+            }
+        }
+
+        self.lightning.retain(|lightning| !lightning.should_destroy());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::load_difficulties;
+
+    const VIEWPORT: Viewport = Viewport {
+        width: 400.0,
+        height: 640.0,
+        wrap: false,
+    };
+    /// Far longer than any run can last, so a stuck world fails instead of hanging.
+    const MAX_STEPS: usize = 120 * 60 * 10;
+
+    fn normal() -> Difficulty {
+        load_difficulties().remove(0)
+    }
+
+    fn input_at(step: usize) -> FrameInput {
+        let mut input = FrameInput::default();
+        input.inputs[Input::Left] = step % 600 < 200;
+        input.inputs[Input::Right] = step % 600 >= 400;
+        input.inputs[Input::BoostRight] = step % 1500 < 10;
+        input
+    }
+
+    fn snapshot(world: &World) -> (f32, Vec2, usize, Vec<Rect>, Vec<Rect>, usize) {
+        (
+            world.time,
+            world.player.position,
+            world.player.balloons(),
+            world.birds.iter().map(|bird| bird.hitbox).collect(),
+            world.fling_things.iter().map(|thing| thing.hitbox).collect(),
+            world.lightning.len(),
+        )
+    }
+
+    #[test]
+    fn runs_until_landing() {
+        let mut world = World::new(&normal(), VIEWPORT, 1);
+        let mut steps = 0;
+        while !world.finished() {
+            world.update(&FrameInput::default(), TIME_STEP);
+            steps += 1;
+            assert!(steps < MAX_STEPS, "run never finished");
+        }
+        assert!(world.time > 0.0);
+        assert!(world.landing().is_some());
+    }
+
+    #[test]
+    fn same_seed_same_run() {
+        let difficulty = normal();
+        let mut a = World::new(&difficulty, VIEWPORT, 99);
+        let mut b = World::new(&difficulty, VIEWPORT, 99);
+        let mut step = 0;
+        while !a.finished() && step < MAX_STEPS {
+            let input = input_at(step);
+            a.update(&input, TIME_STEP);
+            b.update(&input, TIME_STEP);
+            assert_eq!(snapshot(&a), snapshot(&b), "runs split at step {}", step);
+            step += 1;
+        }
+        assert!(b.finished());
+    }
+
+    #[test]
+    fn different_seeds_different_levels() {
+        let difficulty = normal();
+        let a = World::new(&difficulty, VIEWPORT, 1);
+        let b = World::new(&difficulty, VIEWPORT, 2);
+        assert_ne!(snapshot(&a).4, snapshot(&b).4);
+    }
+}